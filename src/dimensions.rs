@@ -0,0 +1,27 @@
+use sdl2::rect::Rect;
+
+/// Size of the canvas everything is rendered to in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimensions {
+    pub width: u32,
+    pub height: u32
+}
+
+impl Dimensions {
+    pub fn new(width: u32, height: u32) -> Dimensions {
+        Dimensions {
+            width: width,
+            height: height
+        }
+    }
+
+    /// Returns the amount of pixels on the canvas.
+    pub fn pixels(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    /// Returns a rectangle covering the whole canvas.
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+}
@@ -0,0 +1,226 @@
+/// How a single 8 x 8 device is mounted relative to the frame.
+///
+/// The orientation maps a pixel in the native coordinates of the device (column `x` is bit `x` of
+/// a row register, row `y` is digit register `8 - y`) onto the part of the frame the device shows.
+/// The coordinates are first transposed and afterwards flipped, if requested.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orientation {
+    transpose: bool,
+    flip_x: bool,
+    flip_y: bool
+}
+
+impl Orientation {
+    /// A device mounted the way the controller expects it.
+    pub fn identity() -> Orientation {
+        Orientation {
+            transpose: false,
+            flip_x: false,
+            flip_y: false
+        }
+    }
+
+    /// A device rotated clockwise by the specified multiple of 90 degrees.
+    pub fn rotation(degrees: u32) -> Result<Orientation, String> {
+        let (transpose, flip_x, flip_y) = match degrees % 360 {
+            0 => (false, false, false),
+            90 => (true, true, false),
+            180 => (false, true, true),
+            270 => (true, false, true),
+            _ => return Err(format!("Rotation by {} degrees is not a multiple of 90 degrees.", degrees))
+        };
+        Ok(Orientation {
+            transpose: transpose,
+            flip_x: flip_x,
+            flip_y: flip_y
+        })
+    }
+
+    /// A device mirrored horizontally.
+    pub fn flip_x() -> Orientation {
+        Orientation {
+            transpose: false,
+            flip_x: true,
+            flip_y: false
+        }
+    }
+
+    /// A device mirrored vertically.
+    pub fn flip_y() -> Orientation {
+        Orientation {
+            transpose: false,
+            flip_x: false,
+            flip_y: true
+        }
+    }
+
+    /// Combine two orientations, applying `other` after this one.
+    pub fn then(&self, other: Orientation) -> Orientation {
+        let (flip_x, flip_y) = if other.transpose {
+            (self.flip_y, self.flip_x)
+        } else {
+            (self.flip_x, self.flip_y)
+        };
+        Orientation {
+            transpose: self.transpose != other.transpose,
+            flip_x: flip_x != other.flip_x,
+            flip_y: flip_y != other.flip_y
+        }
+    }
+
+    /// Map native device coordinates onto coordinates within the area of the frame shown by the device.
+    pub fn apply(&self, x: usize, y: usize) -> (usize, usize) {
+        let (x, y) = if self.transpose { (y, x) } else { (x, y) };
+        (
+            if self.flip_x { 7 - x } else { x },
+            if self.flip_y { 7 - y } else { y }
+        )
+    }
+
+    /// Parse an orientation from a list of transformations separated by `+`, applied in order.
+    ///
+    /// Possible transformations are the rotations `r0`, `r90`, `r180` and `r270` as well as `fx` and
+    /// `fy` for mirroring horizontally and vertically.
+    pub fn parse(description: &str) -> Result<Orientation, String> {
+        description.split('+')
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .fold(Ok(Orientation::identity()), |result, part| {
+                let orientation = try!(result);
+                let transformation = match part {
+                    "fx" => Orientation::flip_x(),
+                    "fy" => Orientation::flip_y(),
+                    _ if part.starts_with('r') => try!(part[1 ..].parse::<u32>()
+                        .map_err(|_| format!("Invalid rotation \"{}\".", part))
+                        .and_then(Orientation::rotation)),
+                    _ => return Err(format!("Unknown transformation \"{}\".", part))
+                };
+                Ok(orientation.then(transformation))
+            })
+    }
+}
+
+/// Which part of the frame is shown by one device in the chain and how it is mounted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeviceMapping {
+    /// Horizontal position of the shown part of the frame in amount of devices.
+    pub x: usize,
+    /// Vertical position of the shown part of the frame in amount of devices.
+    pub y: usize,
+    pub orientation: Orientation
+}
+
+impl DeviceMapping {
+    pub fn new(x: usize, y: usize, orientation: Orientation) -> DeviceMapping {
+        DeviceMapping {
+            x: x,
+            y: y,
+            orientation: orientation
+        }
+    }
+
+    /// Devices chained row by row, each row running from left to right.
+    pub fn row_major(devices_horizontal: usize, devices_vertical: usize) -> Vec<DeviceMapping> {
+        (0 .. devices_vertical).flat_map(|y| {
+            (0 .. devices_horizontal).map(move |x| DeviceMapping::new(x, y, Orientation::identity()))
+        }).collect()
+    }
+
+    /// Devices chained in a zig-zag, every second row running from right to left with the
+    /// devices mounted upside down.
+    pub fn serpentine(devices_horizontal: usize, devices_vertical: usize) -> Vec<DeviceMapping> {
+        (0 .. devices_vertical).flat_map(|y| {
+            (0 .. devices_horizontal).map(move |x| {
+                if y % 2 == 0 {
+                    DeviceMapping::new(x, y, Orientation::identity())
+                } else {
+                    DeviceMapping::new(devices_horizontal - 1 - x, y, Orientation::rotation(180).unwrap())
+                }
+            })
+        }).collect()
+    }
+
+    /// Create the mapping for one of the predefined chain layouts `row-major` and `serpentine`.
+    pub fn layout(name: &str, devices_horizontal: usize, devices_vertical: usize) -> Result<Vec<DeviceMapping>, String> {
+        match name {
+            "row-major" => Ok(DeviceMapping::row_major(devices_horizontal, devices_vertical)),
+            "serpentine" => Ok(DeviceMapping::serpentine(devices_horizontal, devices_vertical)),
+            _ => Err(format!("Unknown chain layout \"{}\".", name))
+        }
+    }
+
+    /// Parse a table of devices in chain order.
+    ///
+    /// Devices are separated by whitespace or `;` and each device is described as `X,Y` or
+    /// `X,Y,ORIENTATION` with the orientation in the format accepted by `Orientation::parse`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Two devices next to each other, chained from right to left and both rotated
+    /// let mapping = DeviceMapping::parse_table("1,0,r90 0,0,r90");
+    /// ```
+    pub fn parse_table(description: &str) -> Result<Vec<DeviceMapping>, String> {
+        description.split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let parts = entry.split(',').collect::<Vec<_>>();
+                if parts.len() < 2 || parts.len() > 3 {
+                    return Err(format!("Invalid device \"{}\", expected X,Y[,ORIENTATION].", entry));
+                }
+                let x = try!(parts[0].parse::<usize>().map_err(|_| format!("Invalid position in \"{}\".", entry)));
+                let y = try!(parts[1].parse::<usize>().map_err(|_| format!("Invalid position in \"{}\".", entry)));
+                let orientation = if parts.len() == 3 {
+                    try!(Orientation::parse(parts[2]))
+                } else {
+                    Orientation::identity()
+                };
+                Ok(DeviceMapping::new(x, y, orientation))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let orientation = Orientation::rotation(90).unwrap();
+        assert_eq!((7, 0), orientation.apply(0, 0));
+        assert_eq!((7, 7), orientation.apply(7, 0));
+        assert_eq!((6, 1), orientation.apply(1, 1));
+        assert_eq!(Orientation::rotation(180).unwrap(), orientation.then(orientation));
+        assert_eq!(Orientation::identity(), Orientation::rotation(270).unwrap().then(orientation));
+    }
+
+    #[test]
+    fn parse_orientation() {
+        let orientation = Orientation::parse("r90+fx").unwrap();
+        assert_eq!((0, 0), orientation.apply(0, 0));
+        assert_eq!((0, 7), orientation.apply(7, 0));
+        assert!(Orientation::parse("r45").is_err());
+        assert!(Orientation::parse("fz").is_err());
+    }
+
+    #[test]
+    fn serpentine() {
+        let upside_down = Orientation::rotation(180).unwrap();
+        assert_eq!(vec![
+            DeviceMapping::new(0, 0, Orientation::identity()),
+            DeviceMapping::new(1, 0, Orientation::identity()),
+            DeviceMapping::new(1, 1, upside_down),
+            DeviceMapping::new(0, 1, upside_down)
+        ], DeviceMapping::serpentine(2, 2));
+    }
+
+    #[test]
+    fn parse_table() {
+        assert_eq!(vec![
+            DeviceMapping::new(1, 0, Orientation::rotation(270).unwrap()),
+            DeviceMapping::new(0, 0, Orientation::flip_y())
+        ], DeviceMapping::parse_table("1,0,r270; 0,0,fy").unwrap());
+        assert!(DeviceMapping::parse_table("1").is_err());
+    }
+}
@@ -0,0 +1,320 @@
+pub mod mapping;
+pub mod transport;
+
+use std::io::{Error, ErrorKind, Result};
+use spidev::Spidev;
+use self::mapping::DeviceMapping;
+use self::transport::Transport;
+
+enum Register {
+    Noop = 0x0,
+    Decodemode = 0x9,
+    Intensity = 0xA,
+    Scanlimit = 0xB,
+    Shutdown = 0xC,
+    Displaytest = 0xF
+}
+
+/// One display consisting of N x M of 8 x 8 LED matrices chained and controlled by MAX7219.
+pub struct Display<T: Transport = Spidev> {
+    devices_horizontal: usize,
+    mapping: Vec<DeviceMapping>,
+    transport: T,
+    /// Rows of each device as they were last sent, `None` if the state of the devices is unknown.
+    shadow: Option<Vec<[u8; 8]>>
+}
+
+impl<T: Transport> Display<T> {
+    /// The transport through which the data is sent.
+    #[cfg(test)]
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Returns the amount of devices connected to this display.
+    fn get_devices(&self) -> usize {
+        self.mapping.len()
+    }
+
+    /// Create a new display width specified dimensions of matrices.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport through which the data is sent to the chain of controllers.
+    /// * `devices_horizontal` - "Width" of the display in horizontal amount of single devices.
+    /// * `devices_vertical` - "Height" of the display in vertical amount of single devices.
+    /// * `mapping` - Part of the frame shown by each device in the chain and its orientation.
+    pub fn new(transport: T,
+            devices_horizontal: usize,
+            devices_vertical: usize,
+            mapping: Vec<DeviceMapping>) -> Result<Display<T>> {
+        if mapping.len() != devices_horizontal * devices_vertical {
+            return Err(Error::new(ErrorKind::InvalidInput, format!(
+                "Mapping describes {} devices, but the display consists of {}.",
+                mapping.len(),
+                devices_horizontal * devices_vertical)));
+        }
+        if let Some(device) = mapping.iter().find(|device| device.x >= devices_horizontal || device.y >= devices_vertical) {
+            return Err(Error::new(ErrorKind::InvalidInput, format!(
+                "Device at {},{} is outside of the display.",
+                device.x,
+                device.y)));
+        }
+        let mut display = Display {
+            devices_horizontal: devices_horizontal,
+            mapping: mapping,
+            transport: transport,
+            shadow: None
+        };
+        let setup_result = display.setup();
+        if !setup_result.is_ok() {
+            Err(setup_result.err().unwrap())
+        } else {
+            Ok(display)
+        }
+    }
+
+    /// Setup the display the way this library will use it.
+    fn setup(&mut self) -> Result<()> {
+        try!(self.write_all(Register::Intensity as u8, 1));
+        try!(self.write_all(Register::Decodemode as u8, 0));
+        try!(self.write_all(Register::Displaytest as u8, 0));
+        try!(self.write_all(Register::Shutdown as u8, 1));
+        try!(self.write_all(Register::Scanlimit as u8, 7));
+        Ok(())
+    }
+
+    /// Convert the part of the frame shown by a device into the data for each of its 8 rows,
+    /// taking the orientation of the device into account.
+    ///
+    /// # Arguments
+    ///
+    /// * `slice` - The whole frame.
+    /// * `device` - The mapping of the device to get the rows for.
+    fn device_rows(&self, slice: &[bool], device: &DeviceMapping) -> [u8; 8] {
+        let width = self.devices_horizontal * 8;
+        let mut rows = [0u8; 8];
+        for (row, data) in rows.iter_mut().enumerate() {
+            for column in 0 .. 8 {
+                let (x, y) = device.orientation.apply(column, row);
+                if slice[(device.y * 8 + y) * width + device.x * 8 + x] {
+                    *data |= 1 << column;
+                }
+            }
+        }
+        rows
+    }
+
+    /// Write a pair of register and data to all devices in the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register of the MAX7219 into which the data should be written.
+    /// * `data` - The data that should be written into the controllers register.
+    pub fn write_all(&mut self, register: u8, data: u8) -> Result<()> {
+        let write_data = (0 .. self.get_devices())
+            .flat_map(|_| vec![register, data])
+            .collect::<Vec<_>>();
+        try!(self.transport.transfer(write_data.as_slice()));
+        Ok(())
+    }
+
+    /// Write a pair of register and data to each of the specified devices in one transfer, sending
+    /// NOOP to all other devices.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register of the MAX7219 into which the data should be written.
+    /// * `data` - The data for each device in the chain or `None` if the device should be skipped.
+    pub fn write_some(&mut self, register: u8, data: &[Option<u8>]) -> Result<()> {
+        let write_data = data.iter().flat_map(|device_data| {
+            match *device_data {
+                Some(data) => vec![register, data],
+                None => vec![Register::Noop as u8, 0]
+            }
+        }).collect::<Vec<_>>();
+        try!(self.transport.transfer(write_data.as_slice()));
+        Ok(())
+    }
+
+    /// Clear the display, Switching all LEDs off.
+    pub fn clear(&mut self) -> Result<()> {
+        for row in 1 .. 9 {
+            try!(self.write_all(row as u8, 0));
+        }
+        self.shadow = Some(vec![[0u8; 8]; self.get_devices()]);
+        Ok(())
+    }
+
+    /// Set the intensity of the whole display.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensity` - The intensity to set. Values from 1 to 16 are possible.
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<()> {
+        try!(self.write_all(Register::Intensity as u8, intensity));
+        Ok(())
+    }
+
+    /// Display a Slice of data on the display.
+    ///
+    /// Only rows which changed since the last frame are sent. Changes to the same row on multiple
+    /// devices are sent within one transfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `slice` - Data to display.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Display a plus on a 1x1 display
+    /// let mut d = Display::new(open_spidev("/dev/spidev0.0", 8_000_000).unwrap(), 1, 1, DeviceMapping::row_major(1, 1));
+    /// d.clear();
+    /// d.display([
+    ///     false, false, false, true, true, false, false, false,
+    ///     false, false, false, true, true, false, false, false,
+    ///     false, false, false, true, true, false, false, false,
+    ///     true, true, true, true, true, true, true, true,
+    ///     true, true, true, true, true, true, true, true,
+    ///     false, false, false, true, true, false, false, false,
+    ///     false, false, false, true, true, false, false, false,
+    ///     false, false, false, true, true, false, false, false
+    /// ]);
+    pub fn display(&mut self, slice: &[bool]) -> Result<()> {
+        let device_rows = self.mapping.iter()
+            .map(|device| self.device_rows(slice, device))
+            .collect::<Vec<_>>();
+        for row in 0 .. 8 {
+            let changes = device_rows.iter().enumerate().map(|(device, rows)| {
+                match self.shadow {
+                    Some(ref shadow) if shadow[device][row] == rows[row] => None,
+                    _ => Some(rows[row])
+                }
+            }).collect::<Vec<_>>();
+            if changes.iter().any(Option::is_some) {
+                try!(self.write_some(0x8u8 - row as u8, &changes));
+            }
+        }
+        self.shadow = Some(device_rows);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mapping::{DeviceMapping, Orientation};
+    use super::transport::Recorder;
+    use test_helpers::*;
+
+    /// Decode the recorded transfers into triples of device, register and data, leaving out NOOPs.
+    fn written(display: &Display<Recorder>) -> Vec<(usize, u8, u8)> {
+        display.transport.transfers.iter().flat_map(|transfer| {
+            transfer.chunks(2)
+                .enumerate()
+                .filter(|&(_, pair)| pair[0] != Register::Noop as u8)
+                .map(|(device, pair)| (device, pair[0], pair[1]))
+                .collect::<Vec<_>>()
+        }).collect()
+    }
+
+    fn lit(display: &Display<Recorder>) -> Vec<(usize, u8, u8)> {
+        written(display).into_iter().filter(|&(_, _, data)| data != 0).collect()
+    }
+
+    #[test]
+    fn setup() {
+        let display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        assert_eq!(vec![
+            vec![0xA, 1, 0xA, 1],
+            vec![0x9, 0, 0x9, 0],
+            vec![0xF, 0, 0xF, 0],
+            vec![0xC, 1, 0xC, 1],
+            vec![0xB, 7, 0xB, 7]
+        ], display.transport.transfers);
+    }
+
+    #[test]
+    fn clear() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.clear().unwrap();
+        assert_eq!((1 .. 9).map(|row| vec![row, 0, row, 0]).collect::<Vec<_>>(), display.transport.transfers);
+    }
+
+    #[test]
+    fn set_intensity() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.set_intensity(5).unwrap();
+        assert_eq!(vec![vec![0xA, 5, 0xA, 5]], display.transport.transfers);
+    }
+
+    #[test]
+    fn display() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(16, 8, &[(0, 0)])).unwrap();
+        assert_eq!(8, display.transport.transfers.len());
+        assert_eq!(vec![8, 0x01, 8, 0], display.transport.transfers[0]);
+        assert_eq!(vec![1, 0, 1, 0], display.transport.transfers[7]);
+    }
+
+    #[test]
+    fn display_unchanged() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(16, 8, &[(0, 0)])).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(16, 8, &[(0, 0)])).unwrap();
+        assert!(display.transport.transfers.is_empty());
+    }
+
+    #[test]
+    fn display_changed_rows() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.clear().unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(16, 8, &[(0, 0), (9, 0), (10, 3)])).unwrap();
+        assert_eq!(vec![
+            vec![8, 0x01, 8, 0x02],
+            vec![0, 0, 5, 0x04]
+        ], display.transport.transfers);
+        display.transport.transfers.clear();
+        display.shadow = None;
+        display.display(&create_test_pixels(16, 8, &[(0, 0), (9, 0), (10, 3)])).unwrap();
+        assert_eq!(8, display.transport.transfers.len());
+    }
+
+    #[test]
+    fn display_row_major() {
+        let mut display = Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(2, 1)).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(16, 8, &[(0, 0), (15, 7)])).unwrap();
+        assert_eq!(vec![(0, 8, 0x01), (1, 1, 0x80)], lit(&display));
+    }
+
+    #[test]
+    fn display_serpentine() {
+        let mut display = Display::new(Recorder::new(), 1, 2, DeviceMapping::serpentine(1, 2)).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(8, 16, &[(0, 8), (1, 15)])).unwrap();
+        assert_eq!(vec![(1, 8, 0x40), (1, 1, 0x80)], lit(&display));
+    }
+
+    #[test]
+    fn display_rotated() {
+        let mut display = Display::new(Recorder::new(), 1, 1, vec![DeviceMapping::new(0, 0, Orientation::rotation(90).unwrap())]).unwrap();
+        display.transport.transfers.clear();
+        display.display(&create_test_pixels(8, 8, &[(7, 0)])).unwrap();
+        assert_eq!(vec![(0, 8, 0x01)], lit(&display));
+    }
+
+    #[test]
+    fn invalid_mapping() {
+        assert!(Display::new(Recorder::new(), 2, 1, DeviceMapping::row_major(1, 1)).is_err());
+        assert!(Display::new(Recorder::new(), 1, 1, vec![DeviceMapping::new(1, 0, Orientation::identity())]).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::io::Result;
+use std::io::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use spidev::{Spidev, SpidevOptions};
+
+/// A sink for the data shifted into the chain of controllers.
+///
+/// Each call to `transfer` is one transfer through the whole chain, after which the controllers
+/// latch the data shifted into them.
+pub trait Transport {
+    /// Send the data for one transfer through the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Pairs of register and data, one for each device in the chain.
+    fn transfer(&mut self, data: &[u8]) -> Result<()>;
+}
+
+impl Transport for Spidev {
+    fn transfer(&mut self, data: &[u8]) -> Result<()> {
+        self.write_all(data)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn transfer(&mut self, data: &[u8]) -> Result<()> {
+        (**self).transfer(data)
+    }
+}
+
+/// Open and configure the SPI device the controllers are connected to.
+///
+/// # Arguments
+///
+/// * `path` - Path of the SPI device, for example `/dev/spidev0.0`.
+/// * `speed` - Clock speed in Hz. Long cables might require a slower clock.
+pub fn open_spidev<P: AsRef<Path>>(path: P, speed: u32) -> Result<Spidev> {
+    let mut device = try!(Spidev::open(path));
+    let mut options = SpidevOptions::new();
+    options.bits_per_word(8);
+    options.max_speed_hz(speed);
+    try!(device.configure(&options));
+    Ok(device)
+}
+
+/// Keeps all transfers in memory.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Recorder {
+    pub transfers: Vec<Vec<u8>>
+}
+
+#[cfg(test)]
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder {
+            transfers: Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+impl Transport for Recorder {
+    fn transfer(&mut self, data: &[u8]) -> Result<()> {
+        self.transfers.push(data.to_vec());
+        Ok(())
+    }
+}
+
+/// Writes all transfers to a file or FIFO, one directly after another.
+///
+/// As every transfer contains one pair of bytes for each device in the chain, the transfers can be
+/// split up again by the reader.
+pub struct FileTransport {
+    file: File
+}
+
+impl FileTransport {
+    /// Open the file for writing, creating it if it doesn't exist yet.
+    ///
+    /// An existing file is truncated, so it only contains the transfers of this run. Truncating
+    /// has no effect on a FIFO.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileTransport> {
+        let file = try!(OpenOptions::new().write(true).create(true).truncate(true).open(path));
+        Ok(FileTransport {
+            file: file
+        })
+    }
+}
+
+impl Transport for FileTransport {
+    fn transfer(&mut self, data: &[u8]) -> Result<()> {
+        try!(self.file.write_all(data));
+        self.file.flush()
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::io::Result;
+use std::io::prelude::*;
+
+/// Amount of bits per pixel, resulting in 16 shades of grey.
+const BITS_PER_PIXEL: u8 = 4;
+const MAX_CODE: u16 = 4095;
+
+/// Collects codes of variable width into bytes, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            bits: 0
+        }
+    }
+
+    fn write(&mut self, code: u16, width: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress the indices of the pixels using the variable width LZW variant used by GIF.
+fn compress(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear_code = 1u16 << min_code_size;
+    let end_code = clear_code + 1;
+    let mut writer = BitWriter::new();
+    let mut dictionary: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end_code + 1;
+    let mut width = min_code_size + 1;
+    let mut prefix: Option<u16> = None;
+    writer.write(clear_code, width);
+    for &index in indices {
+        prefix = match prefix {
+            None => Some(index as u16),
+            Some(code) => match dictionary.get(&(code, index)).cloned() {
+                Some(extended) => Some(extended),
+                None => {
+                    writer.write(code, width);
+                    dictionary.insert((code, index), next_code);
+                    if next_code >= 1 << width {
+                        width += 1;
+                    }
+                    next_code += 1;
+                    if next_code == MAX_CODE {
+                        writer.write(clear_code, width);
+                        dictionary.clear();
+                        next_code = end_code + 1;
+                        width = min_code_size + 1;
+                    }
+                    Some(index as u16)
+                }
+            }
+        };
+    }
+    if let Some(code) = prefix {
+        writer.write(code, width);
+        // The decoder adds one more entry after reading the last code, which might widen the codes
+        if next_code > end_code + 1 && next_code == 1 << width {
+            width += 1;
+        }
+    }
+    writer.write(end_code, width);
+    writer.finish()
+}
+
+/// Writes an animated, endlessly looping GIF with 16 shades of grey.
+///
+/// The GIF is only complete once `finish` was called.
+pub struct GifEncoder<W: Write> {
+    writer: W,
+    width: u16,
+    height: u16
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Create a new encoder, writing the header right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write the GIF to.
+    /// * `width` - Width of the frames in pixels.
+    /// * `height` - Height of the frames in pixels.
+    pub fn new(mut writer: W, width: u16, height: u16) -> Result<GifEncoder<W>> {
+        try!(writer.write_all(b"GIF89a"));
+        try!(writer.write_all(&[width as u8, (width >> 8) as u8, height as u8, (height >> 8) as u8]));
+        // Global color table with 2^BITS_PER_PIXEL entries, background color and aspect ratio
+        try!(writer.write_all(&[0x80 | (BITS_PER_PIXEL - 1), 0, 0]));
+        let colors = 1u16 << BITS_PER_PIXEL;
+        for color in 0 .. colors {
+            let shade = (255 - color * 255 / (colors - 1)) as u8;
+            try!(writer.write_all(&[shade, shade, shade]));
+        }
+        // Loop forever
+        try!(writer.write_all(&[0x21, 0xFF, 0x0B]));
+        try!(writer.write_all(b"NETSCAPE2.0"));
+        try!(writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00]));
+        Ok(GifEncoder {
+            writer: writer,
+            width: width,
+            height: height
+        })
+    }
+
+    /// Append a frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensities` - The intensity of each pixel from 0 (off) to 255 (on).
+    /// * `delay` - How long to show the frame in hundredths of a second.
+    pub fn frame(&mut self, intensities: &[u8], delay: u16) -> Result<()> {
+        let (width, height) = (self.width, self.height);
+        try!(self.writer.write_all(&[0x21, 0xF9, 0x04, 0x00, delay as u8, (delay >> 8) as u8, 0x00, 0x00]));
+        try!(self.writer.write_all(&[0x2C, 0, 0, 0, 0]));
+        try!(self.writer.write_all(&[width as u8, (width >> 8) as u8, height as u8, (height >> 8) as u8, 0x00]));
+        let indices = intensities.iter()
+            .map(|intensity| intensity >> (8 - BITS_PER_PIXEL))
+            .collect::<Vec<_>>();
+        try!(self.writer.write_all(&[BITS_PER_PIXEL]));
+        for block in compress(BITS_PER_PIXEL, &indices).chunks(255) {
+            try!(self.writer.write_all(&[block.len() as u8]));
+            try!(self.writer.write_all(block));
+        }
+        try!(self.writer.write_all(&[0x00]));
+        self.writer.flush()
+    }
+
+    /// Write the trailer ending the GIF, after which no more frames may be appended.
+    pub fn finish(&mut self) -> Result<()> {
+        try!(self.writer.write_all(&[0x3B]));
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Straightforward GIF LZW decoder to check the compressed data against.
+    fn decompress(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear_code = 1u16 << min_code_size;
+        let end_code = clear_code + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut width = min_code_size + 1;
+        let mut previous: Option<u16> = None;
+        let mut result = Vec::new();
+        let (mut buffer, mut bits, mut position) = (0u32, 0u8, 0usize);
+        loop {
+            while bits < width {
+                buffer |= (data[position] as u32) << bits;
+                position += 1;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as u16;
+            buffer >>= width;
+            bits -= width;
+            if code == clear_code {
+                table = (0 .. end_code + 1).map(|index| vec![index as u8]).collect();
+                width = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == end_code {
+                return result;
+            }
+            let entry = if (code as usize) < table.len() {
+                table[code as usize].clone()
+            } else {
+                let mut entry = table[previous.unwrap() as usize].clone();
+                let first = entry[0];
+                entry.push(first);
+                entry
+            };
+            if let Some(previous) = previous {
+                let mut added = table[previous as usize].clone();
+                added.push(entry[0]);
+                table.push(added);
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            result.extend(entry.iter().cloned());
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn finish() {
+        let mut encoder = super::GifEncoder::new(Vec::new(), 2, 1).unwrap();
+        encoder.frame(&[0, 255], 3).unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(b"GIF89a", &encoder.writer[.. 6]);
+        assert_eq!(Some(&0x3B), encoder.writer.last());
+    }
+
+    #[test]
+    fn compress() {
+        let inputs = vec![
+            vec![0],
+            vec![1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2],
+            (0 .. 512).map(|index| if (index / 3) % 2 == 0 { 15 } else { 0 }).collect::<Vec<u8>>(),
+            (0 .. 8192).map(|index| ((index * 7 + index / 13) % 16) as u8).collect::<Vec<u8>>()
+        ];
+        for input in inputs {
+            assert_eq!(input, decompress(4, &super::compress(4, &input)));
+        }
+    }
+}
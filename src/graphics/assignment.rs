@@ -0,0 +1,250 @@
+use nalgebra::Vector2;
+use std::i64;
+
+/// Factor by which the slack of the bids shrinks between the rounds of the auction.
+const EPSILON_FACTOR: i64 = 4;
+
+fn manhattan(a: &Vector2<f32>, b: &Vector2<f32>) -> i64 {
+    ((a.x - b.x).abs() + (a.y - b.y).abs()) as i64
+}
+
+/// The point closest to `point` by Manhattan distance.
+fn nearest<'a>(point: &Vector2<f32>, candidates: &'a [Vector2<f32>]) -> Option<&'a Vector2<f32>> {
+    candidates.iter().min_by_key(|candidate| manhattan(point, candidate))
+}
+
+/// Assign each row to a different column, so that the total cost is minimal, using the auction
+/// algorithm with ε-scaling.
+///
+/// Unassigned rows bid for their most profitable column, raising its price by how much better it is
+/// than the next best column plus ε, which outbids its previous owner. Each round starts over with a
+/// smaller ε, keeping the prices. Costs are scaled by one more than the amount of columns, so that
+/// the assignment of the last round with ε = 1 is optimal for the original costs.
+///
+/// # Arguments
+///
+/// * `costs` - Cost of assigning each row to each column, with no more rows than columns.
+/// * `columns` - Amount of columns.
+fn auction(costs: &[Vec<i64>], columns: usize) -> Vec<usize> {
+    let rows = costs.len();
+    let scale = columns as i64 + 1;
+    // Additional rows which cost nothing make the problem square, every column gets an owner then
+    let cost = |row: usize, column: usize| if row < rows { costs[row][column] * scale } else { 0 };
+    let max_cost = costs.iter().flat_map(|row_costs| row_costs.iter()).cloned().max().unwrap_or(0);
+    let mut prices = vec![0i64; columns];
+    let mut owners = vec![None; columns];
+    let mut epsilon = (max_cost * scale / EPSILON_FACTOR).max(1);
+    loop {
+        for owner in owners.iter_mut() {
+            *owner = None;
+        }
+        let mut unassigned = (0 .. columns).rev().collect::<Vec<_>>();
+        while let Some(row) = unassigned.pop() {
+            let (mut best_column, mut best_value, mut second_value) = (0, i64::MIN, i64::MIN);
+            for column in 0 .. columns {
+                let value = -cost(row, column) - prices[column];
+                if value > best_value {
+                    second_value = best_value;
+                    best_value = value;
+                    best_column = column;
+                } else if value > second_value {
+                    second_value = value;
+                }
+            }
+            prices[best_column] += if second_value == i64::MIN { epsilon } else { best_value - second_value + epsilon };
+            if let Some(outbid) = owners[best_column] {
+                unassigned.push(outbid);
+            }
+            owners[best_column] = Some(row);
+        }
+        if epsilon == 1 {
+            break;
+        }
+        epsilon = (epsilon / EPSILON_FACTOR).max(1);
+    }
+    let mut result = vec![0; rows];
+    for (column, owner) in owners.into_iter().enumerate() {
+        if let Some(row) = owner {
+            if row < rows {
+                result[row] = column;
+            }
+        }
+    }
+    result
+}
+
+/// Pair the lit pixels of two scenes, so that the total Manhattan distance the pixels walk is minimal.
+///
+/// As many pixels as possible are paired one to one. If the scenes have a different amount of lit
+/// pixels, each remaining pixel of the new scene splits off the closest pixel of the old one, and
+/// each remaining pixel of the old scene merges into the closest pixel of the new one. Pixels of the
+/// old scene walk off the display if the new scene is empty.
+///
+/// The pairs of all pixels of the new scene come first, in their order, followed by those of the
+/// remaining pixels of the old scene.
+pub fn assign(origin: &[Vector2<f32>], target: &[Vector2<f32>]) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    if origin.is_empty() {
+        return target.iter().map(|&point| (point, point)).collect();
+    }
+    if target.is_empty() {
+        return origin.iter().map(|&point| (point, Vector2::new(-1.0f32, -1.0f32))).collect();
+    }
+    // The smaller set makes up the rows
+    let swapped = origin.len() > target.len();
+    let (rows, columns) = if swapped { (target, origin) } else { (origin, target) };
+    let costs = rows.iter()
+        .map(|row| columns.iter().map(|column| manhattan(row, column)).collect())
+        .collect::<Vec<Vec<i64>>>();
+    let assignment = auction(&costs, columns.len());
+    // The origin paired with each target, if any
+    let mut target_origins: Vec<Option<Vector2<f32>>> = vec![None; target.len()];
+    let mut origin_assigned = vec![false; origin.len()];
+    for (row, &column) in assignment.iter().enumerate() {
+        let (origin_index, target_index) = if swapped { (column, row) } else { (row, column) };
+        target_origins[target_index] = Some(origin[origin_index]);
+        origin_assigned[origin_index] = true;
+    }
+    let mut result = target.iter().zip(target_origins).map(|(target_point, paired)| {
+        (paired.unwrap_or_else(|| *nearest(target_point, origin).unwrap()), *target_point)
+    }).collect::<Vec<_>>();
+    for (origin_point, _) in origin.iter().zip(origin_assigned).filter(|&(_, assigned)| !assigned) {
+        result.push((*origin_point, *nearest(origin_point, target).unwrap()));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::*;
+
+    #[test]
+    fn assign_equal_size() {
+        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0), Vector2::new(3.0, 4.0)];
+        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0), Vector2::new(3.0, 10.0)];
+        let result = assign(&origin, &target);
+        assert_eq!(vec![
+                   (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
+                   (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
+                   (Vector2::new(3.0, 4.0), Vector2::new(3.0, 10.0))
+        ], result)
+    }
+
+    #[test]
+    fn assign_smaller_size() {
+        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0)];
+        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0), Vector2::new(3.0, 10.0)];
+        let result = assign(&origin, &target);
+        assert_eq!(vec![
+                   (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
+                   (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
+                   (Vector2::new(2.0, 4.0), Vector2::new(3.0, 10.0))
+        ], result)
+    }
+
+    #[test]
+    fn assign_bigger_size() {
+        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0), Vector2::new(3.0, 4.0)];
+        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0)];
+        let result = assign(&origin, &target);
+        assert_eq!(vec![
+           (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
+           (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
+           (Vector2::new(3.0, 4.0), Vector2::new(2.0, 10.0))
+        ], result)
+    }
+
+    #[test]
+    fn assign_optimal() {
+        // Greedily, both targets would take the first origin, as it is closest to each of them
+        let origin = vec![Vector2::new(1.0, 0.0), Vector2::new(10.0, 0.0)];
+        let target = vec![Vector2::new(2.0, 0.0), Vector2::new(0.0, 0.0)];
+        assert_eq!(vec![
+           (Vector2::new(10.0, 0.0), Vector2::new(2.0, 0.0)),
+           (Vector2::new(1.0, 0.0), Vector2::new(0.0, 0.0))
+        ], assign(&origin, &target));
+    }
+
+    #[test]
+    fn assign_empty() {
+        let points = vec![Vector2::new(1.0, 4.0)];
+        assert_eq!(vec![(points[0], points[0])], assign(&[], &points));
+        assert_eq!(vec![(points[0], Vector2::new(-1.0, -1.0))], assign(&points, &[]));
+    }
+
+    #[test]
+    fn assign_full_panel() {
+        let panel = (0 .. 32 * 16).map(|index| Vector2::new((index % 32) as f32, (index / 32) as f32)).collect::<Vec<_>>();
+        let result = assign(&panel, &panel);
+        assert!(result.iter().all(|&(origin, target)| origin == target));
+        // Every pixel of the lower half walks to a different pixel of the full panel
+        let result = assign(&panel[256 ..], &panel);
+        assert_eq!(512, result.len());
+        let mut origins = result.iter().map(|&(origin, _)| (origin.x as i32, origin.y as i32)).collect::<Vec<_>>();
+        origins.sort();
+        origins.dedup();
+        assert_eq!(256, origins.len());
+        assert!(result[256 ..].iter().all(|&(origin, target)| origin == target));
+    }
+
+    /// Lowest total cost of assigning the rows from `row` on to the columns not used yet, trying
+    /// every assignment.
+    fn brute_force(costs: &[Vec<i64>], row: usize, used: &mut Vec<bool>) -> i64 {
+        if row == costs.len() {
+            return 0;
+        }
+        let mut best = i64::max_value();
+        for column in 0 .. used.len() {
+            if !used[column] {
+                used[column] = true;
+                best = best.min(costs[row][column] + brute_force(costs, row + 1, used));
+                used[column] = false;
+            }
+        }
+        best
+    }
+
+    fn total_cost(costs: &[Vec<i64>], assignment: &[usize]) -> i64 {
+        assignment.iter().enumerate().map(|(row, &column)| costs[row][column]).sum()
+    }
+
+    #[test]
+    fn auction_minimal_cost() {
+        let costs = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(vec![1, 0, 2], auction(&costs, 3));
+        let costs = vec![vec![7, 1, 5, 9], vec![1, 8, 2, 9]];
+        assert_eq!(vec![1, 0], auction(&costs, 4));
+    }
+
+    #[test]
+    fn auction_brute_force() {
+        // Three pixels of an old scene which all are closest to the same corner of 70 new ones
+        let rows = vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0)];
+        let columns = (0 .. 70).map(|index| Vector2::new((index % 10 * 2 + 1) as f32, (index / 10 + 1) as f32))
+            .collect::<Vec<_>>();
+        let costs = rows.iter()
+            .map(|row| columns.iter().map(|column| manhattan(row, column)).collect())
+            .collect::<Vec<Vec<i64>>>();
+        let assignment = auction(&costs, columns.len());
+        let mut columns_used = assignment.clone();
+        columns_used.sort();
+        columns_used.dedup();
+        assert_eq!(3, columns_used.len());
+        assert_eq!(brute_force(&costs, 0, &mut vec![false; columns.len()]), total_cost(&costs, &assignment));
+        // Square costs without any structure
+        let costs = (0 .. 7).map(|row| (0 .. 7).map(|column| (row * 37 + column * 11 + row * column * 5) % 23).collect())
+            .collect::<Vec<Vec<i64>>>();
+        assert_eq!(brute_force(&costs, 0, &mut vec![false; 7]), total_cost(&costs, &auction(&costs, 7)));
+    }
+
+    #[test]
+    fn auction_checkerboard() {
+        // Every pixel needs to walk at least one step to a neighbour, which all of them can
+        let panel = (0 .. 32 * 16).map(|index| Vector2::new((index % 32) as f32, (index / 32) as f32)).collect::<Vec<_>>();
+        let (black, white): (Vec<Vector2<f32>>, Vec<Vector2<f32>>) = panel.iter().partition(|point| (point.x + point.y) as i32 % 2 == 0);
+        let costs = black.iter()
+            .map(|row| white.iter().map(|column| manhattan(row, column)).collect())
+            .collect::<Vec<Vec<i64>>>();
+        assert_eq!(256, total_cost(&costs, &auction(&costs, white.len())));
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Instant;
+
+/// Monotonic clock all animations are based on, so that they look the same at any frame rate and
+/// regardless of how often the information about the music is updated.
+pub struct Clock {
+    start: Instant
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            start: Instant::now()
+        }
+    }
+
+    /// Milliseconds since the clock was created.
+    pub fn now(&self) -> u64 {
+        let elapsed = Instant::now().duration_since(self.start);
+        elapsed.as_secs() * 1000 + (elapsed.subsec_nanos() / 1_000_000) as u64
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use super::*;
+
+    #[test]
+    fn now() {
+        let clock = Clock::new();
+        let start = clock.now();
+        thread::sleep(Duration::from_millis(20));
+        assert!(clock.now() >= start + 20);
+    }
+}
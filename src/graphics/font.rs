@@ -1,6 +1,9 @@
 use sdl2::render::{Texture, Renderer};
 use sdl2::rect::{Point, Rect};
 
+/// Pixels per second the text of a marquee scrolls by.
+const MARQUEE_SPEED: u64 = 20;
+
 pub struct FontRenderer {
     pub width: u32,
     pub height: u32,
@@ -16,10 +19,20 @@ impl FontRenderer {
         }
     }
 
-    pub fn marquee(&self, text: &str, start: &Point, ms: u64, renderer: &mut Renderer) -> Result<(), String> {
-        let full_width = (self.width as i32 + 1) * text.len() as i32;
-        let time_index = (ms / 50) as i32;
-        let x = 32 - time_index % (full_width + 32);
+    /// Returns the width in pixels the specified text will occupy when rendered.
+    pub fn text_width(&self, text: &str) -> u32 {
+        ((self.width + 1) * text.len() as u32).saturating_sub(1)
+    }
+
+    /// Scroll the text from the right to the left through the specified width, over and over.
+    ///
+    /// # Arguments
+    ///
+    /// * `ms` - Milliseconds since the text started scrolling in.
+    pub fn marquee(&self, text: &str, start: &Point, width: u32, ms: u64, renderer: &mut Renderer) -> Result<(), String> {
+        let full_width = (self.width + 1) as u64 * text.len() as u64;
+        let scrolled = ms * MARQUEE_SPEED / 1000;
+        let x = width as i32 - (scrolled % (full_width + width as u64)) as i32;
         let point = start.offset(x as i32, 0);
         self.text(point, text, renderer)
     }
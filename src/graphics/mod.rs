@@ -1,121 +1,175 @@
+pub mod assignment;
+pub mod clock;
 pub mod font;
+pub mod playlist;
 pub mod scene;
+pub mod transition;
 
 use sdl2::render::{Renderer, Texture};
-use sdl2::rect::{Rect, Point};
 use sdl2::pixels::{Color, PixelFormatEnum};
-use mpd::status::State;
 use spectrum::SpectrumResult;
 use self::scene::*;
+use self::playlist::{Condition, Playlist, PlaylistEntry, SceneKind};
+use self::transition::{Transition, TransitionConfig, TransitionKind};
+use mpd::status::State;
 use nalgebra::Vector2;
-use nalgebra::Norm;
-use core::cmp::Ordering;
-use std::mem::replace;
+use std::mem::{replace, swap};
 use info::Info;
-use std;
+use dimensions::Dimensions;
 
-const SCENE_TIME: u64 = 20_000;
-const TRANSITION_FRAMES: u64 = 4;
+pub use self::scene::spectrum::SpectrumStyle;
 
+/// Milliseconds after which every transition is finished regardless of its configured duration,
+/// which are 120 frames of the render loop at 16 ms each.
+const MAX_TRANSITION_DURATION: u64 = 1920;
+/// Milliseconds the transition into and out of the announcement takes.
+const ANNOUNCEMENT_TRANSITION_DURATION: u64 = 1000;
+
+/// How the scenes are drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphicsConfig {
+    pub spectrum: SpectrumStyle,
+    pub playlist: Playlist,
+    /// Milliseconds the media scene is shown after the song or the playback state changed,
+    /// interrupting the playlist, `None` to not announce changes.
+    pub announcement: Option<u64>
+}
 
 pub struct SceneContainer {
     scene: Box<Scene>,
     texture: Texture,
-    condition: Box<Fn(&Info) -> bool>
+    /// When and how the scene is shown.
+    entry: PlaylistEntry
 }
 
 impl SceneContainer {
-    fn new(scene: Box<Scene>, texture: Texture, condition: Box<Fn(&Info) -> bool>) -> SceneContainer {
+    fn new(scene: Box<Scene>, texture: Texture, entry: PlaylistEntry) -> SceneContainer {
         SceneContainer {
             texture: texture,
             scene: scene,
-            condition: condition
+            entry: entry
         }
     }
 }
 
-
-trait ToSdlPoint {
-    fn to_sdl(&self) -> Point;
-}
-
-impl ToSdlPoint for Vector2<f32> {
-    fn to_sdl(&self) -> Point {
-        Point::new(self.x as i32, self.y as i32)
-    }
+/// A transition in progress.
+struct ActiveTransition {
+    transition: Box<Transition>,
+    /// Time at which the transition started.
+    started: u64,
+    /// Milliseconds the transition takes.
+    duration: u64
 }
 
 pub struct Graphics {
-    frames_in_transition: u64,
-    time_in_scene: u64,
+    dimensions: Dimensions,
+    connected: bool,
+    /// Time of the animation clock for the frame being drawn.
     time: u64,
+    /// Time at which the current scene was switched to.
+    scene_started: u64,
+    /// Time at which the current scene was shown after its transition, from which its animations start.
+    scene_shown: u64,
     scenes: Vec<SceneContainer>,
     current_scene: Option<SceneContainer>,
-    transition: Option<Vec<(Vector2<f32>, Vector2<f32>)>>
+    /// The last frame of the previous scene, shown during the transition.
+    previous_texture: Texture,
+    transition: Option<ActiveTransition>,
+    /// The scene announcing changes of the track or the playback state, `None` while it is the
+    /// current scene or if changes are not announced.
+    announcement: Option<SceneContainer>,
+    /// Whether the current scene is the announcement.
+    announcing: bool,
+    /// Id of the song and playback state of the previous frame, `None` while disconnected.
+    playing: Option<(Option<u32>, State)>
+}
+
+fn prepare_texture(renderer: &mut Renderer, dimensions: Dimensions) -> Texture {
+    renderer.create_texture_target(PixelFormatEnum::RGBA8888, dimensions.width, dimensions.height).unwrap()
 }
 
-fn prepare_texture(renderer: &mut Renderer) -> Texture {
-    renderer.create_texture_target(PixelFormatEnum::RGBA8888, 32, 16).unwrap()
+/// Take the next entry of the queue, which is stored in reverse, whose condition matches. The
+/// entries skipped on the way are queued up again behind all others.
+///
+/// Returns `None` and leaves the queue as it is if no entry matches.
+fn take_matching<T, F>(queue: &mut Vec<T>, matches: F) -> Option<T> where F: Fn(&T) -> bool {
+    let position = match queue.iter().rposition(|item| matches(item)) {
+        Some(position) => position,
+        None => return None
+    };
+    let mut skipped = queue.split_off(position + 1);
+    let item = queue.pop();
+    skipped.extend(queue.drain(..));
+    *queue = skipped;
+    item
+}
+
+/// Whether to announce the change from the song and playback state of the previous frame to the
+/// current ones, each `None` while disconnected.
+///
+/// Stopping is not announced, as the media scene has nothing to show then, and neither is the state
+/// found when connecting.
+fn is_announced(previous: Option<(Option<u32>, State)>, current: Option<(Option<u32>, State)>) -> bool {
+    match (previous, current) {
+        (Some(previous), Some(current)) => previous != current && current.1 != State::Stop,
+        _ => false
+    }
+}
+
+fn create_scene(kind: SceneKind, renderer: &mut Renderer, dimensions: Dimensions, config: &GraphicsConfig) -> Box<Scene> {
+    match kind {
+        SceneKind::Time => Box::new(SceneTime::new(renderer, dimensions)),
+        SceneKind::Media => Box::new(SceneMedia::new(renderer, dimensions)),
+        SceneKind::Spectrum => Box::new(SceneSpectrum::new(renderer, dimensions, config.spectrum)),
+        SceneKind::Amplitude => Box::new(SceneAmplitude::new(renderer, dimensions)),
+        SceneKind::Offline => Box::new(SceneOffline::new(renderer, dimensions))
+    }
 }
 
 impl Graphics {
-    pub fn new(renderer: &mut Renderer, time: u64) -> Graphics {
-        let scenes = vec![
-            SceneContainer::new(Box::new(SceneTime::new(renderer)),
-                prepare_texture(renderer),
-                Box::new(|_| true)),
-            SceneContainer::new(Box::new(SceneMedia::new(renderer)),
-                prepare_texture(renderer),
-                Box::new(|info| info.state != State::Stop)),
-            SceneContainer::new(Box::new(SceneSpectrum::new(renderer)),
-                prepare_texture(renderer),
-                Box::new(|info| info.state == State::Play)),
-            SceneContainer::new(Box::new(SceneAmplitude::new(renderer)),
-                prepare_texture(renderer),
-                Box::new(|info| info.state == State::Play))
-        ];
+    pub fn new(renderer: &mut Renderer, dimensions: Dimensions, config: GraphicsConfig) -> Graphics {
+        // The scenes are taken from the back of the queue, so the playlist is stored in reverse
+        let scenes = config.playlist.entries.iter().rev().map(|entry| {
+            SceneContainer::new(create_scene(entry.scene, renderer, dimensions, &config),
+                prepare_texture(renderer, dimensions),
+                entry.clone())
+        }).collect();
+        let announcement = config.announcement.map(|duration| {
+            SceneContainer::new(create_scene(SceneKind::Media, renderer, dimensions, &config),
+                prepare_texture(renderer, dimensions),
+                PlaylistEntry {
+                    scene: SceneKind::Media,
+                    duration: duration,
+                    condition: Condition::Always,
+                    transition: TransitionConfig {
+                        kind: TransitionKind::Migration,
+                        duration: ANNOUNCEMENT_TRANSITION_DURATION
+                    }
+                })
+        });
         Graphics {
-            frames_in_transition: 0,
-            time_in_scene: 0,
-            time: time,
+            dimensions: dimensions,
+            connected: true,
+            time: 0,
+            scene_started: 0,
+            scene_shown: 0,
             scenes: scenes,
+            previous_texture: prepare_texture(renderer, dimensions),
             transition: None,
-            current_scene: None
+            current_scene: None,
+            announcement: announcement,
+            announcing: false,
+            playing: None
         }
     }
 
-    fn create_transition(origin: Vec<Vector2<f32>>, target: Vec<Vector2<f32>>) -> Vec<(Vector2<f32>, Vector2<f32>)> {
-        let mut leftover_origins = origin.clone();
-        let mut result: Vec<(Vector2<f32>, Vector2<f32>)> = Vec::new();
-        for target_point in &target {
-            let min = (&origin).iter().min_by(|a, b| {
-                let dist_a = (*a - target_point).norm();
-                let dist_b = (*b - target_point).norm();
-                dist_a.partial_cmp(&dist_b).unwrap_or(Ordering::Equal)
-            });
-            if min.is_some() {
-                leftover_origins.retain(|point| point != min.unwrap());
-            }
-            result.push((*min.unwrap_or(target_point), *target_point));
-        }
-        for origin_point in leftover_origins {
-            let min = (&target).iter().min_by(|a, b| {
-                let dist_a = (*a - origin_point).norm();
-                let dist_b = (*b - origin_point).norm();
-                dist_a.partial_cmp(&dist_b).unwrap_or(Ordering::Equal)
-            });
-            result.push((origin_point, *min.unwrap_or(&Vector2::new(-1.0f32, -1.0f32))));
-        }
-        result
-    }
-
-
-    fn derasterize_pixels(renderer: &Renderer) -> Result<Vec<Vector2<f32>>, String> {
+    fn derasterize_pixels(renderer: &Renderer, dimensions: Dimensions) -> Result<Vec<Vector2<f32>>, String> {
         let pixels = try!(renderer.read_pixels(None, PixelFormatEnum::RGBA8888));
+        let width = dimensions.width as usize;
         let mut result: Vec<Vector2<f32>> = Vec::new();
-        for x in 0 .. 32 {
-            for y in 0 .. 16 {
-                let index = (x + y * 32) * 4;
+        for x in 0 .. width {
+            for y in 0 .. dimensions.height as usize {
+                let index = (x + y * width) * 4;
                 if pixels[index] == 255 {
                     result.push(Vector2::new(x as f32, y as f32));
                 }
@@ -124,61 +178,28 @@ impl Graphics {
         Ok(result)
     }
 
-    fn approach(a: f32, b: f32) -> f32 {
-        if (a - b).abs() < std::f32::EPSILON {
-            a
-        } else if a > b {
-            a - 1.0
-        } else {
-            a + 1.0
-        }
+    /// Milliseconds the current scene has been shown, which its animations are based on.
+    fn scene_time(&self) -> u64 {
+        self.time.saturating_sub(self.scene_shown)
     }
 
-    fn perform_transition(&mut self) {
-        if self.transition.is_none() {
-            return;
-        }
-        let transition = self.transition.clone().unwrap();
-        let mut changed = false;
-        let new_transition = transition.iter().map(|&(origin, target)| {
-            if origin == target {
-                (origin, target)
+    fn draw_transition(&mut self, renderer: &mut Renderer) -> Result<(), String> {
+        let time = self.time;
+        let finished = {
+            let active = self.transition.as_mut().unwrap();
+            let duration = active.duration.min(MAX_TRANSITION_DURATION);
+            let progress = if duration == 0 {
+                1.0
             } else {
-                let mut x = Graphics::approach(origin.x, target.x);
-                let mut y = Graphics::approach(origin.y, target.y);
-                if (x - origin.x).abs() > std::f32::EPSILON || (y - origin.y).abs() > std::f32::EPSILON {
-                    changed = true;
-                }
-                if (x - origin.x).abs() > std::f32::EPSILON && (y - origin.y).abs() > std::f32::EPSILON {
-                    if self.time % 2 == 1 {
-                        x = origin.x;
-                    } else {
-                        y = origin.y;
-                    }
-                }
-                (Vector2::new(x, y), target)
-            }
-        }).collect::<Vec<(Vector2<f32>, Vector2<f32>)>>();
-        self.transition = if changed {
-            Some(new_transition)
-        } else {
-            None
+                (time.saturating_sub(active.started) as f32 / duration as f32).min(1.0)
+            };
+            let new_texture = &self.current_scene.as_ref().unwrap().texture;
+            try!(active.transition.draw(renderer, &self.previous_texture, new_texture, progress));
+            progress >= 1.0
         };
-    }
-
-    fn draw_transition(&mut self, renderer: &mut Renderer) -> Result<(), String> {
-        renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
-        renderer.clear();
-        let origins = self.transition.clone()
-            .unwrap()
-            .iter()
-            .map(|&(origin, _)| origin.to_sdl())
-            .collect::<Vec<Point>>();
-        try!(renderer.render_target().unwrap().reset());
-        renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
-        try!(renderer.draw_points(&origins));
-        if self.frames_in_transition % TRANSITION_FRAMES == 0 {
-            self.perform_transition();
+        if finished {
+            self.transition = None;
+            self.scene_shown = time;
         }
         Ok(())
     }
@@ -187,7 +208,8 @@ impl Graphics {
             renderer: &mut Renderer,
             info: &Info,
             spectrum: &SpectrumResult,
-            time: u64) -> Result<(SceneContainer, Vec<Vector2<f32>>), String> {
+            time: u64,
+            dimensions: Dimensions) -> Result<(SceneContainer, Vec<Vector2<f32>>), String> {
         try!(renderer.render_target().unwrap().set(container.texture));
         // Clear the texture
         renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
@@ -195,46 +217,71 @@ impl Graphics {
         renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
         // Draw current scene once, to get an up-to-date version of the pixels 
         try!(container.scene.draw(renderer, info, spectrum, time));
-        let pixels = try!(Graphics::derasterize_pixels(renderer));
+        let pixels = try!(Graphics::derasterize_pixels(renderer, dimensions));
         let new_texture = renderer.render_target().unwrap().reset().unwrap().unwrap();
-        Ok((SceneContainer::new(container.scene, new_texture, container.condition), pixels))
+        Ok((SceneContainer::new(container.scene, new_texture, container.entry), pixels))
     }
 
+    /// Switch to the next scene of the playlist whose condition matches, or to the announcement.
+    ///
+    /// The current scene is kept if no other scene matches.
     fn next_scene(
             &mut self,
             renderer: &mut Renderer,
             info: &Info,
-            spectrum: &SpectrumResult) -> Result<(), String> {
-        // Return old scene into front of queue and grep derasterized pixels of it
+            spectrum: &SpectrumResult,
+            announce: bool) -> Result<(), String> {
+        let container = if announce {
+            self.announcement.take().expect("No announcement to show.")
+        } else {
+            match take_matching(&mut self.scenes, |container| container.entry.condition.matches(info)) {
+                Some(container) => container,
+                None => return Ok(())
+            }
+        };
+        // Return old scene into front of queue, or put the announcement aside, and grep derasterized
+        // pixels of it
         let old_pixels = if self.current_scene.is_some() {
             let scene = replace(&mut self.current_scene, None);
-            let (swapped_scene, pixels) = Graphics::get_pixels_of_scene(scene.unwrap(),
+            let scene_time = self.scene_time();
+            let (mut swapped_scene, pixels) = Graphics::get_pixels_of_scene(scene.unwrap(),
                 renderer,
                 info,
                 spectrum,
-                self.time_in_scene).expect("Unabled to read pixels from scene.");
-            self.scenes.insert(0, swapped_scene);
-            pixels
+                scene_time,
+                self.dimensions).expect("Unabled to read pixels from scene.");
+            // Keep the last frame of the old scene for the transition
+            swap(&mut swapped_scene.texture, &mut self.previous_texture);
+            if self.announcing {
+                self.announcement = Some(swapped_scene);
+            } else {
+                self.scenes.insert(0, swapped_scene);
+            }
+            Some(pixels)
         } else {
-            Vec::new()
+            None
         };
-        // Take buffers from top and return them to front if condition not matching
-        // until a scene with a matching condition was found
-        let mut container = self.scenes.pop().unwrap();
-        while !(container.condition)(info) {
-            self.scenes.insert(0, container);
-            container = self.scenes.pop().unwrap();
-        }
-        // Grab derasterized pixels of new scene
+        self.announcing = announce;
+        // Grab derasterized pixels of new scene as of the start of its animations
         let (swapped_container, new_pixels) = Graphics::get_pixels_of_scene(
             container,
             renderer,
             info,
             spectrum,
-            self.time_in_scene).expect("Error when reading pixels from scene.");
+            0,
+            self.dimensions).expect("Error when reading pixels from scene.");
+        // Transition from the old scene to the new one, the first scene is shown right away
+        let config = swapped_container.entry.transition;
+        let (dimensions, time) = (self.dimensions, self.time);
+        self.transition = old_pixels.map(|old_pixels| ActiveTransition {
+            transition: transition::create(config.kind, old_pixels, new_pixels, dimensions, time as u32),
+            started: time,
+            duration: config.duration
+        });
         // Store that one as current scene
-        self.transition = Some(Graphics::create_transition(old_pixels, new_pixels));
         self.current_scene = Some(swapped_container);
+        self.scene_started = time;
+        self.scene_shown = time;
         Ok(())
     }
 
@@ -244,7 +291,7 @@ impl Graphics {
             info: &Info,
             spectrum: &SpectrumResult) -> Option<SceneContainer> {
         if self.current_scene.is_none() {
-            self.next_scene(renderer, info, spectrum).expect("Could not switch to next scene.");
+            self.next_scene(renderer, info, spectrum, false).expect("Could not switch to next scene.");
         }
         replace(&mut self.current_scene, None)
     }
@@ -254,104 +301,137 @@ impl Graphics {
     }
 
     fn draw_scene(&mut self, renderer: &mut Renderer, info: &Info, spectrum: &SpectrumResult) -> Result<(), String> {
-        let mut container = self.take_current_scene(renderer, info, spectrum).unwrap();
+        let container = self.take_current_scene(renderer, info, spectrum);
         renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
         // Clear window texture
         renderer.clear();
+        // Stay blank until the condition of a scene matches
+        let mut container = match container {
+            Some(container) => container,
+            None => return Ok(())
+        };
         try!(renderer.render_target().unwrap().set(container.texture));
         // Clear scene texture
         renderer.clear();
         renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
         // Draw current scene 
-        try!(container.scene.draw(renderer, info, spectrum, self.time_in_scene));
+        try!(container.scene.draw(renderer, info, spectrum, self.scene_time()));
         // Reset texture back to wondow texture
         let updated_scene_texture = renderer.render_target().unwrap().reset().unwrap().unwrap();
         // Render the scene texture onto the window texture
-        try!(renderer.copy(&updated_scene_texture, Some(Rect::new(0, 0, 32, 16)), Some(Rect::new(0, 0, 32, 16))));
-        self.give_current_scene_back(SceneContainer::new(container.scene, updated_scene_texture, container.condition));
+        try!(renderer.copy(&updated_scene_texture, Some(self.dimensions.rect()), Some(self.dimensions.rect())));
+        self.give_current_scene_back(SceneContainer::new(container.scene,
+            updated_scene_texture,
+            container.entry));
         Ok(())
     }
 
-    pub fn draw(&mut self, renderer: &mut Renderer, info: Info, spectrum: SpectrumResult) -> Result<(), String> {
-        // Switch scene after timeout
-        if info.ms % SCENE_TIME < self.time % SCENE_TIME {
-            try!(self.next_scene(renderer, &info, &spectrum));
+    /// Draw the next frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Milliseconds on the animation clock of the render loop, which all animations are
+    ///            based on so that they do not depend on the frame rate.
+    pub fn draw(&mut self,
+            renderer: &mut Renderer,
+            info: Info,
+            spectrum: SpectrumResult,
+            time: u64) -> Result<(), String> {
+        self.time = time;
+        let playing = if info.connected {
+            Some((info.song_id, info.state))
+        } else {
+            None
+        };
+        let changed = is_announced(self.playing, playing);
+        self.playing = playing;
+        // Switch scene after its duration or immediately if the connection to MPD was lost or restored
+        let scene_over = match self.current_scene {
+            Some(ref container) => time >= self.scene_started + container.entry.duration,
+            None => false
+        };
+        if changed && self.announcing {
+            // Show the announcement for its full duration again
+            self.scene_started = time;
+        } else if changed && self.announcement.is_some() {
+            try!(self.next_scene(renderer, &info, &spectrum, true));
+        } else if scene_over || info.connected != self.connected {
+            try!(self.next_scene(renderer, &info, &spectrum, false));
         }
-        let time_delta = info.ms - self.time;
+        self.connected = info.connected;
         // Render transition if transition is in progress and else render scene
-        let result = if self.transition.is_some() {
-            self.frames_in_transition += self.frames_in_transition;
+        if self.transition.is_some() {
             self.draw_transition(renderer)
         } else {
-            self.time_in_scene += time_delta;
             self.draw_scene(renderer, &info, &spectrum)
-        };
-        self.time = info.ms;
-        result
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use sdl2::rect::Rect;
+    use mpd::status::State;
     use nalgebra::Vector2;
     use test_helpers::*;
+    use dimensions::Dimensions;
     use super::*;
-    use std;
 
     #[test]
-    fn derasterize_pixels() {
-        let mut renderer = create_test_renderer();
-        renderer.draw_rect(Rect::new(4, 4, 2, 2)).unwrap();
-        let pixels = Graphics::derasterize_pixels(&renderer).unwrap();
-        assert_eq!(vec![
-                   Vector2::new(4.0, 4.0),
-                   Vector2::new(4.0, 5.0),
-                   Vector2::new(5.0, 4.0),
-                   Vector2::new(5.0, 5.0)
-        ], pixels);
+    fn take_matching() {
+        let entries = Playlist::parse("
+scenes:
+  - scene: time
+    condition: playing
+  - scene: media
+    condition: paused
+  - scene: spectrum
+    condition: playing
+").unwrap().entries;
+        // The queue is stored in reverse
+        let mut queue = entries.iter().rev().cloned().collect::<Vec<_>>();
+        let paused = create_test_info(true, State::Pause, 100);
+        assert_eq!(Some(entries[1].clone()), super::take_matching(&mut queue, |entry| entry.condition.matches(&paused)));
+        assert_eq!(vec![entries[0].clone(), entries[2].clone()], queue);
     }
 
     #[test]
-    fn create_transition_equal_size() {
-        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0), Vector2::new(3.0, 4.0)];
-        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0), Vector2::new(3.0, 10.0)];
-        let result = Graphics::create_transition(origin, target);
-        assert_eq!(vec![
-                   (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
-                   (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
-                   (Vector2::new(3.0, 4.0), Vector2::new(3.0, 10.0))
-        ], result)
+    fn take_matching_none() {
+        let entries = Playlist::parse("
+scenes:
+  - scene: time
+    condition: playing
+  - scene: media
+    condition: paused
+").unwrap().entries;
+        let mut queue = entries.iter().rev().cloned().collect::<Vec<_>>();
+        let stopped = create_test_info(true, State::Stop, 100);
+        assert_eq!(None, super::take_matching(&mut queue, |entry| entry.condition.matches(&stopped)));
+        assert_eq!(vec![entries[1].clone(), entries[0].clone()], queue);
     }
 
     #[test]
-    fn create_transition_smaller_size() {
-        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0)];
-        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0), Vector2::new(3.0, 10.0)];
-        let result = Graphics::create_transition(origin, target);
-        assert_eq!(vec![
-                   (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
-                   (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
-                   (Vector2::new(2.0, 4.0), Vector2::new(3.0, 10.0))
-        ], result)
+    fn is_announced() {
+        // Songs without tags are told apart by their id
+        assert!(super::is_announced(Some((Some(1), State::Play)), Some((Some(2), State::Play))));
+        assert!(super::is_announced(Some((Some(1), State::Play)), Some((Some(1), State::Pause))));
+        assert!(super::is_announced(Some((None, State::Stop)), Some((Some(1), State::Play))));
+        assert!(!super::is_announced(Some((Some(1), State::Play)), Some((Some(1), State::Play))));
+        assert!(!super::is_announced(Some((Some(1), State::Play)), Some((Some(1), State::Stop))));
+        assert!(!super::is_announced(None, Some((Some(1), State::Play))));
+        assert!(!super::is_announced(Some((Some(1), State::Play)), None));
     }
 
     #[test]
-    fn create_transition_bigger_size() {
-        let origin = vec![Vector2::new(1.0, 4.0), Vector2::new(2.0, 4.0), Vector2::new(3.0, 4.0)];
-        let target = vec![Vector2::new(1.0, 10.0), Vector2::new(2.0, 10.0)];
-        let result = Graphics::create_transition(origin, target);
+    fn derasterize_pixels() {
+        let mut renderer = create_test_renderer();
+        renderer.draw_rect(Rect::new(4, 4, 2, 2)).unwrap();
+        let pixels = Graphics::derasterize_pixels(&renderer, Dimensions::new(32, 16)).unwrap();
         assert_eq!(vec![
-           (Vector2::new(1.0, 4.0), Vector2::new(1.0, 10.0)),
-           (Vector2::new(2.0, 4.0), Vector2::new(2.0, 10.0)),
-           (Vector2::new(3.0, 4.0), Vector2::new(2.0, 10.0))
-        ], result)
-    }
-
-    #[test]
-    fn approach() {
-        assert!(Graphics::approach(5.0, 9.0) - 6.0 < std::f32::EPSILON);
-        assert!(Graphics::approach(7.0, 3.0) - 6.0 < std::f32::EPSILON);
-        assert!(Graphics::approach(6.0, 6.0) - 6.0 < std::f32::EPSILON);
+                   Vector2::new(4.0, 4.0),
+                   Vector2::new(4.0, 5.0),
+                   Vector2::new(5.0, 4.0),
+                   Vector2::new(5.0, 5.0)
+        ], pixels);
     }
 }
@@ -0,0 +1,343 @@
+use std::fs::File;
+use std::io::Read;
+use mpd::status::State;
+use yaml_rust::{Yaml, YamlLoader};
+use info::Info;
+use graphics::transition::{TransitionConfig, TransitionKind};
+
+/// Seconds a scene is shown if its entry does not specify a duration.
+const DEFAULT_DURATION: f64 = 20.0;
+/// Seconds the transition into a scene takes if its entry does not specify a duration.
+const DEFAULT_TRANSITION_DURATION: f64 = 1.0;
+
+/// The scenes which can be shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneKind {
+    Time,
+    Media,
+    Spectrum,
+    Amplitude,
+    Offline
+}
+
+impl SceneKind {
+    pub fn parse(name: &str) -> Result<SceneKind, String> {
+        match name {
+            "time" => Ok(SceneKind::Time),
+            "media" => Ok(SceneKind::Media),
+            "spectrum" => Ok(SceneKind::Spectrum),
+            "amplitude" => Ok(SceneKind::Amplitude),
+            "offline" => Ok(SceneKind::Offline),
+            _ => Err(format!("Unknown scene \"{}\".", name))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater
+}
+
+impl Comparison {
+    fn compare(&self, left: i64, right: i64) -> bool {
+        match *self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right
+        }
+    }
+}
+
+/// When a scene may be shown, parsed from expressions like `playing and volume>0`.
+///
+/// Expressions consist of the keywords `always`, `connected`, `disconnected`, `playing`, `paused` and
+/// `stopped` or comparisons of the volume, which can be negated with `not` and combined with `and`,
+/// which binds stronger than `or`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    Always,
+    Connected,
+    Disconnected,
+    State(State),
+    Volume(Comparison, i64),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>)
+}
+
+impl Condition {
+    pub fn parse(expression: &str) -> Result<Condition, String> {
+        let alternatives = try!(expression.split(" or ").map(|alternative| {
+            let terms = try!(alternative.split(" and ").map(Condition::parse_term).collect::<Result<Vec<_>, _>>());
+            Ok(if terms.len() == 1 { terms[0].clone() } else { Condition::All(terms) })
+        }).collect::<Result<Vec<_>, String>>());
+        Ok(if alternatives.len() == 1 { alternatives[0].clone() } else { Condition::Any(alternatives) })
+    }
+
+    fn parse_term(term: &str) -> Result<Condition, String> {
+        let term = term.trim();
+        if term.starts_with("not ") {
+            return Ok(Condition::Not(Box::new(try!(Condition::parse_term(&term[4 ..])))));
+        }
+        match term {
+            "always" => return Ok(Condition::Always),
+            "connected" => return Ok(Condition::Connected),
+            "disconnected" => return Ok(Condition::Disconnected),
+            "playing" => return Ok(Condition::State(State::Play)),
+            "paused" => return Ok(Condition::State(State::Pause)),
+            "stopped" => return Ok(Condition::State(State::Stop)),
+            _ => ()
+        }
+        if term.starts_with("volume") {
+            let comparison = term[6 ..].trim_left();
+            // Operators of two characters first, so that ">=" is not taken for ">"
+            let operators = [
+                ("<=", Comparison::LessOrEqual),
+                (">=", Comparison::GreaterOrEqual),
+                ("!=", Comparison::NotEqual),
+                ("<", Comparison::Less),
+                (">", Comparison::Greater),
+                ("=", Comparison::Equal)
+            ];
+            for &(operator, comparison_kind) in &operators {
+                if comparison.starts_with(operator) {
+                    let value = comparison[operator.len() ..].trim();
+                    let value = try!(value.parse::<i64>().map_err(|_| format!("Invalid volume \"{}\".", value)));
+                    return Ok(Condition::Volume(comparison_kind, value));
+                }
+            }
+        }
+        Err(format!("Unknown condition \"{}\".", term))
+    }
+
+    /// Whether the scene may be shown for the current information. Conditions about the playback
+    /// never match while disconnected from MPD.
+    pub fn matches(&self, info: &Info) -> bool {
+        match *self {
+            Condition::Always => true,
+            Condition::Connected => info.connected,
+            Condition::Disconnected => !info.connected,
+            Condition::State(ref state) => info.connected && info.state == *state,
+            Condition::Volume(comparison, value) => info.connected && comparison.compare(info.volume as i64, value),
+            Condition::Not(ref condition) => !condition.matches(info),
+            Condition::All(ref conditions) => conditions.iter().all(|condition| condition.matches(info)),
+            Condition::Any(ref conditions) => conditions.iter().any(|condition| condition.matches(info))
+        }
+    }
+}
+
+/// A scene in the playlist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaylistEntry {
+    pub scene: SceneKind,
+    /// Milliseconds the scene is shown before switching to the next one.
+    pub duration: u64,
+    pub condition: Condition,
+    /// The transition leading into the scene.
+    pub transition: TransitionConfig
+}
+
+/// Which scenes are shown in which order, for how long and under which condition.
+///
+/// The playlist is read from a YAML file like:
+///
+/// ```yaml
+/// scenes:
+///   - scene: spectrum
+///     duration: 30
+///     condition: playing and volume>0
+///     transition: slide-left
+///     transition-duration: 0.5
+///   - scene: time
+/// ```
+///
+/// The durations are given in seconds and default to 20 for the scene and 1 for the transition, the
+/// condition defaults to `always` and the transition to `migration`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playlist {
+    pub entries: Vec<PlaylistEntry>
+}
+
+impl Default for Playlist {
+    /// The rotation used without a playlist file.
+    fn default() -> Playlist {
+        Playlist::parse("
+scenes:
+  - scene: time
+    condition: connected
+  - scene: offline
+    condition: disconnected
+  - scene: amplitude
+    condition: playing
+  - scene: spectrum
+    condition: playing
+  - scene: media
+    condition: playing or paused
+").expect("The default playlist is invalid.")
+    }
+}
+
+impl Playlist {
+    /// Read the playlist from the YAML file at the specified path.
+    pub fn load(path: &str) -> Result<Playlist, String> {
+        let mut content = String::new();
+        try!(File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .map_err(|err| format!("Unable to read playlist \"{}\": {}", path, err)));
+        Playlist::parse(&content).map_err(|err| format!("Invalid playlist \"{}\": {}", path, err))
+    }
+
+    pub fn parse(content: &str) -> Result<Playlist, String> {
+        let documents = try!(YamlLoader::load_from_str(content).map_err(|err| err.to_string()));
+        let scenes = match documents.get(0).and_then(|document| document["scenes"].as_vec()) {
+            Some(scenes) => scenes,
+            None => return Err(String::from("The playlist needs to contain a list of scenes."))
+        };
+        let entries = try!(scenes.iter().enumerate().map(|(index, entry)| {
+            Playlist::parse_entry(entry).map_err(|err| format!("Scene {} of the playlist: {}", index + 1, err))
+        }).collect::<Result<Vec<_>, _>>());
+        if entries.is_empty() {
+            return Err(String::from("The playlist needs to contain at least one scene."));
+        }
+        Ok(Playlist {
+            entries: entries
+        })
+    }
+
+    fn parse_entry(entry: &Yaml) -> Result<PlaylistEntry, String> {
+        let scene = match entry["scene"].as_str() {
+            Some(scene) => try!(SceneKind::parse(scene)),
+            None => return Err(String::from("Each entry of the playlist needs to name a scene."))
+        };
+        let duration = try!(Playlist::parse_seconds(&entry["duration"], DEFAULT_DURATION));
+        // Also rejects durations which are not a number or would be shown for less than a millisecond
+        if !(duration * 1000.0 >= 1.0) || !duration.is_finite() {
+            return Err(format!("The duration of a scene needs to be at least a millisecond, not {}.", duration));
+        }
+        let transition_duration = try!(Playlist::parse_seconds(&entry["transition-duration"], DEFAULT_TRANSITION_DURATION));
+        if !(transition_duration >= 0.0) || !transition_duration.is_finite() {
+            return Err(format!("The duration of a transition needs to be zero or positive, not {}.", transition_duration));
+        }
+        let transition = match entry["transition"] {
+            Yaml::BadValue => TransitionKind::Migration,
+            ref value => match value.as_str() {
+                Some(name) => try!(TransitionKind::parse(name)),
+                None => return Err(String::from("The transition of a scene needs to be a name."))
+            }
+        };
+        let condition = match entry["condition"] {
+            Yaml::BadValue => Condition::Always,
+            ref value => match value.as_str() {
+                Some(expression) => try!(Condition::parse(expression)),
+                None => return Err(String::from("The condition of a scene needs to be an expression."))
+            }
+        };
+        Ok(PlaylistEntry {
+            scene: scene,
+            duration: (duration * 1000.0) as u64,
+            condition: condition,
+            transition: TransitionConfig {
+                kind: transition,
+                duration: (transition_duration * 1000.0) as u64
+            }
+        })
+    }
+
+    /// Read a duration in seconds, which may be an integer or a real number.
+    fn parse_seconds(value: &Yaml, default: f64) -> Result<f64, String> {
+        match *value {
+            Yaml::BadValue => Ok(default),
+            Yaml::Integer(seconds) => Ok(seconds as f64),
+            ref value => value.as_f64().ok_or_else(|| String::from("Durations need to be a number of seconds."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpd::status::State;
+    use test_helpers::*;
+    use graphics::transition::{Direction, TransitionConfig, TransitionKind};
+    use super::*;
+
+    #[test]
+    fn parse_condition() {
+        assert_eq!(Condition::State(State::Play), Condition::parse("playing").unwrap());
+        assert_eq!(Condition::Volume(Comparison::GreaterOrEqual, 50), Condition::parse("volume >= 50").unwrap());
+        assert_eq!(Condition::Any(vec![
+            Condition::All(vec![Condition::State(State::Play), Condition::Volume(Comparison::Greater, 0)]),
+            Condition::Not(Box::new(Condition::Connected))
+        ]), Condition::parse("playing and volume>0 or not connected").unwrap());
+        assert!(Condition::parse("dancing").is_err());
+        assert!(Condition::parse("volume>loud").is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let condition = Condition::parse("playing and volume>0").unwrap();
+        assert!(condition.matches(&create_test_info(true, State::Play, 10)));
+        assert!(!condition.matches(&create_test_info(true, State::Play, 0)));
+        assert!(!condition.matches(&create_test_info(true, State::Pause, 10)));
+        assert!(!Condition::parse("stopped").unwrap().matches(&create_test_info(false, State::Stop, 0)));
+        assert!(Condition::parse("disconnected").unwrap().matches(&create_test_info(false, State::Stop, 0)));
+    }
+
+    #[test]
+    fn parse_playlist() {
+        let playlist = Playlist::parse("
+scenes:
+  - scene: spectrum
+    duration: 2.5
+    condition: playing
+    transition: wipe-down
+    transition-duration: 0
+  - scene: time
+").unwrap();
+        assert_eq!(vec![
+            PlaylistEntry {
+                scene: SceneKind::Spectrum,
+                duration: 2500,
+                condition: Condition::State(State::Play),
+                transition: TransitionConfig {
+                    kind: TransitionKind::Wipe(Direction::Down),
+                    duration: 0
+                }
+            },
+            PlaylistEntry {
+                scene: SceneKind::Time,
+                duration: 20_000,
+                condition: Condition::Always,
+                transition: TransitionConfig {
+                    kind: TransitionKind::Migration,
+                    duration: 1000
+                }
+            }
+        ], playlist.entries);
+        assert_eq!(5, Playlist::default().entries.len());
+    }
+
+    #[test]
+    fn parse_invalid_playlist() {
+        assert!(Playlist::parse("scenes: []").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: disco").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    duration: -1").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    duration: 0").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    duration: 0.0004").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    duration: .nan").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    duration: .inf").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    transition-duration: .nan").is_err());
+        assert_eq!(Err(String::from("Scene 2 of the playlist: The duration of a scene needs to be at least a millisecond, not 0.")),
+            Playlist::parse("scenes:\n  - scene: time\n  - scene: media\n    duration: 0").map(|_| ()));
+        assert!(Playlist::parse("scenes:\n  - duration: 1").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    condition: sometimes").is_err());
+        assert!(Playlist::parse("scenes:\n  - scene: time\n    transition: teleport").is_err());
+    }
+}
@@ -4,12 +4,17 @@ use sdl2::pixels::Color;
 use info::Info;
 use spectrum::SpectrumResult;
 use graphics::scene::Scene;
+use dimensions::Dimensions;
 
-pub struct SceneAmplitude { }
+pub struct SceneAmplitude {
+    dimensions: Dimensions
+}
 
 impl SceneAmplitude {
-    pub fn new(_: &mut Renderer) -> SceneAmplitude {
-        SceneAmplitude { }
+    pub fn new(_: &mut Renderer, dimensions: Dimensions) -> SceneAmplitude {
+        SceneAmplitude {
+            dimensions: dimensions
+        }
     }
 }
 
@@ -18,9 +23,11 @@ impl Scene for SceneAmplitude {
         renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
         renderer.clear();
         renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
+        let max_height = (self.dimensions.height - 1) as f32;
+        let bottom = self.dimensions.height as i32;
         let points = spectrum.amplitude.iter().enumerate().flat_map(|(x, value)| {
-            let height = value[1] * 15.0;
-            vec![Rect::new(x as i32, 16i32 - height.max(0.0f32) as i32, 1, height as u32)]
+            let height = value[1] * max_height;
+            vec![Rect::new(x as i32, bottom - height.max(0.0f32) as i32, 1, height as u32)]
         }).collect::<Vec<Rect>>();
         renderer.draw_rects(&points)
     }
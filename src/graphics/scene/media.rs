@@ -3,27 +3,35 @@ use mpd::status::State;
 use sdl2::render::{Renderer, Texture};
 use sdl2_image::LoadTexture;
 use sdl2::rect::{Point, Rect};
+use sdl2::pixels::Color;
 use info::Info;
 use spectrum::SpectrumResult;
 use graphics::scene::Scene;
 use graphics::font::FontRenderer;
+use dimensions::Dimensions;
 
 const SPINNER_FRAMES: i32 = 32;
+/// Milliseconds the spinner takes to turn by one frame.
+const SPINNER_INTERVAL: u64 = 100;
 const STATE_SIZE: u32 = 5;
 const SPINNER_SIZE: u32 = 9;
+/// Height of the spinner and the marquee below it including the gap between them.
+const FULL_LAYOUT_HEIGHT: u32 = 16;
 
 pub struct SceneMedia {
+    dimensions: Dimensions,
     font_3x5: FontRenderer,
     spinner: Texture,
     playback_state: Texture
 }
 
 impl SceneMedia {
-    pub fn new(renderer: &mut Renderer) -> SceneMedia {
+    pub fn new(renderer: &mut Renderer, dimensions: Dimensions) -> SceneMedia {
         let font_3x5 = FontRenderer::new(3, 5, renderer.load_texture(Path::new("assets/3x5.png")).unwrap());
         let spinner = renderer.load_texture(Path::new("assets/spinner.png")).unwrap();
         let playback_state = renderer.load_texture(Path::new("assets/playback-state.png")).unwrap();
         SceneMedia {
+            dimensions: dimensions,
             font_3x5: font_3x5,
             spinner: spinner,
             playback_state: playback_state
@@ -31,28 +39,30 @@ impl SceneMedia {
     }
 }
 
-impl Scene for SceneMedia {
-    fn draw(&mut self,
-            renderer: &mut Renderer,
-            info: &Info,
-            _: &SpectrumResult,
-            time: u64) -> Result<(), String> {
+impl SceneMedia {
+    fn state_frame(info: &Info) -> i32 {
+        match info.state {
+            State::Play => 0,
+            State::Pause => 1,
+            State::Stop => 2
+        }
+    }
+
+    /// Spinner showing the progress with the playback state inside and the marquee below.
+    fn draw_full(&self, renderer: &mut Renderer, info: &Info, time: u64) -> Result<(), String> {
+        let top = (self.dimensions.height - FULL_LAYOUT_HEIGHT) as i32 / 2;
+        let left = (self.dimensions.width as i32 - SPINNER_SIZE as i32) / 2;
         let media_text = format!("{} - {}", info.artist, info.song);
-        try!(self.font_3x5.marquee(media_text.as_str(), &Point::new(0, 11), time, renderer));
+        try!(self.font_3x5.marquee(media_text.as_str(), &Point::new(0, top + 11), self.dimensions.width, time, renderer));
         let elapsed = info.elapsed.num_milliseconds() / 100;
         let duration = info.duration.num_milliseconds() / 100;
         let progress = elapsed as f32 / duration as f32;
         let pixels = (progress * SPINNER_FRAMES as f32) as i32;
-        let start = (time as i32 / 100) % SPINNER_FRAMES;
-        let state_frame = match info.state {
-            State::Play => 0,
-            State::Pause => 1,
-            State::Stop => 2
-        };
+        let start = ((time / SPINNER_INTERVAL) % SPINNER_FRAMES as u64) as i32;
         for i in 0 .. pixels {
             let frame = (start + i) % SPINNER_FRAMES;
             let src_pos = Point::new(frame * SPINNER_SIZE as i32, 0 as i32);
-            let dest_pos = Point::new(11, 0);
+            let dest_pos = Point::new(left, top);
             try!(renderer.copy(
                 &self.spinner,
                 Some(Rect::new(src_pos.x(), src_pos.y(), SPINNER_SIZE, SPINNER_SIZE)),
@@ -61,8 +71,38 @@ impl Scene for SceneMedia {
         }
         renderer.copy(
             &self.playback_state,
-            Some(Rect::new(state_frame * STATE_SIZE as i32, 0, STATE_SIZE, STATE_SIZE)),
-            Some(Rect::new(13, 2, STATE_SIZE, STATE_SIZE))
+            Some(Rect::new(SceneMedia::state_frame(info) * STATE_SIZE as i32, 0, STATE_SIZE, STATE_SIZE)),
+            Some(Rect::new(left + 2, top + 2, STATE_SIZE, STATE_SIZE))
         )
     }
+
+    /// Playback state on the left and the marquee next to it, for displays too low for the spinner.
+    fn draw_compact(&self, renderer: &mut Renderer, info: &Info, time: u64) -> Result<(), String> {
+        let top = (self.dimensions.height as i32 - STATE_SIZE as i32) / 2;
+        let media_text = format!("{} - {}", info.artist, info.song);
+        try!(self.font_3x5.marquee(media_text.as_str(), &Point::new(0, top), self.dimensions.width, time, renderer));
+        // Keep the marquee from scrolling through the playback state
+        renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
+        try!(renderer.fill_rect(Rect::new(0, 0, STATE_SIZE + 1, self.dimensions.height)));
+        renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
+        renderer.copy(
+            &self.playback_state,
+            Some(Rect::new(SceneMedia::state_frame(info) * STATE_SIZE as i32, 0, STATE_SIZE, STATE_SIZE)),
+            Some(Rect::new(0, top, STATE_SIZE, STATE_SIZE))
+        )
+    }
+}
+
+impl Scene for SceneMedia {
+    fn draw(&mut self,
+            renderer: &mut Renderer,
+            info: &Info,
+            _: &SpectrumResult,
+            time: u64) -> Result<(), String> {
+        if self.dimensions.height >= FULL_LAYOUT_HEIGHT {
+            self.draw_full(renderer, info, time)
+        } else {
+            self.draw_compact(renderer, info, time)
+        }
+    }
 }
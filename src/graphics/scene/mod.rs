@@ -1,10 +1,12 @@
 pub mod amplitude;
 pub mod media;
+pub mod offline;
 pub mod spectrum;
 pub mod time;
 
 pub use self::amplitude::SceneAmplitude;
 pub use self::media::SceneMedia;
+pub use self::offline::SceneOffline;
 pub use self::spectrum::SceneSpectrum;
 pub use self::time::SceneTime;
 
@@ -13,6 +15,11 @@ use info::Info;
 use spectrum::SpectrumResult;
 
 pub trait Scene {
+    /// Draw the scene onto the current render target.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Milliseconds since the scene was shown, which animations are based on.
     fn draw(&mut self,
             renderer: &mut Renderer,
             info: &Info,
@@ -0,0 +1,41 @@
+use std::path::Path;
+use sdl2::render::Renderer;
+use sdl2_image::LoadTexture;
+use sdl2::rect::Point;
+use info::Info;
+use spectrum::SpectrumResult;
+use graphics::scene::Scene;
+use graphics::font::FontRenderer;
+use dimensions::Dimensions;
+
+const DOTS: i32 = 4;
+const DOT_INTERVAL: u64 = 400;
+const TEXT: &'static str = "NO MPD";
+
+pub struct SceneOffline {
+    dimensions: Dimensions,
+    font_3x5: FontRenderer
+}
+
+impl SceneOffline {
+    pub fn new(renderer: &mut Renderer, dimensions: Dimensions) -> SceneOffline {
+        let font_3x5 = FontRenderer::new(3, 5, renderer.load_texture(Path::new("assets/3x5.png")).unwrap());
+        SceneOffline {
+            dimensions: dimensions,
+            font_3x5: font_3x5
+        }
+    }
+}
+
+impl Scene for SceneOffline {
+    fn draw(&mut self, renderer: &mut Renderer, _: &Info, _: &SpectrumResult, time: u64) -> Result<(), String> {
+        let left = (self.dimensions.width as i32 - self.font_3x5.text_width(TEXT) as i32) / 2;
+        let top = (self.dimensions.height as i32 - 12).max(0) / 2;
+        try!(self.font_3x5.text(Point::new(left, top), TEXT, renderer));
+        // Animate a row of dots while waiting for the connection to come back
+        let dots = (time / DOT_INTERVAL) as i32 % (DOTS + 1);
+        let dots_left = (self.dimensions.width as i32 - (DOTS - 1) * 4) / 2;
+        let points = (0 .. dots).map(|dot| Point::new(dots_left + dot * 4, top + 9)).collect::<Vec<Point>>();
+        renderer.draw_points(&points)
+    }
+}
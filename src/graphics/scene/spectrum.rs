@@ -1,14 +1,103 @@
 use sdl2::render::Renderer;
-use sdl2::rect::Rect;
+use sdl2::rect::{Point, Rect};
+use sdl2::pixels::Color;
 use info::Info;
 use spectrum::SpectrumResult;
 use graphics::scene::Scene;
+use dimensions::Dimensions;
 
-pub struct SceneSpectrum { }
+/// Intensity of the bar below the range of a column, which is only visible when dithering.
+const RANGE_BASE_INTENSITY: u8 = 64;
+
+/// How the bars of the spectrum move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrumStyle {
+    /// Full heights per second a bar falls at, while it rises instantly.
+    pub fall_rate: f32,
+    /// Seconds the highest level of a column is held as a dot, `None` to show no peaks.
+    pub peak_hold: Option<f32>,
+    /// Full heights per second a peak falls at once it was held.
+    pub peak_fall_rate: f32,
+    /// Highlight the range between the lowest and highest level within each column above a dimmed
+    /// bar from the bottom.
+    pub show_range: bool
+}
+
+/// Level of a column, falling slowly after it was reached.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bar {
+    /// Lowest level, only used when showing the range.
+    min: f32,
+    max: f32,
+    peak: f32,
+    /// Seconds since the peak was reached.
+    peak_age: f32
+}
+
+impl Bar {
+    fn new() -> Bar {
+        Bar {
+            min: 0.0,
+            max: 0.0,
+            peak: 0.0,
+            peak_age: 0.0
+        }
+    }
+
+    /// Follow the current levels of the column.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - Current lowest level from 0 to 1.
+    /// * `max` - Current highest level from 0 to 1.
+    /// * `elapsed` - Seconds since the last update.
+    fn update(&mut self, min: f32, max: f32, elapsed: f32, style: &SpectrumStyle) {
+        let fall = style.fall_rate * elapsed;
+        self.max = max.max(self.max - fall);
+        self.min = min.max(self.min - fall).min(self.max);
+        if self.max >= self.peak {
+            self.peak = self.max;
+            self.peak_age = 0.0;
+        } else {
+            self.peak_age += elapsed;
+            if self.peak_age > style.peak_hold.unwrap_or(0.0) {
+                self.peak = (self.peak - style.peak_fall_rate * elapsed).max(self.max);
+            }
+        }
+    }
+}
+
+pub struct SceneSpectrum {
+    dimensions: Dimensions,
+    style: SpectrumStyle,
+    bars: Vec<Bar>,
+    /// Time of the last drawn frame.
+    time: Option<u64>
+}
 
 impl SceneSpectrum {
-    pub fn new(_: &mut Renderer) -> SceneSpectrum {
-        SceneSpectrum { }
+    pub fn new(_: &mut Renderer, dimensions: Dimensions, style: SpectrumStyle) -> SceneSpectrum {
+        SceneSpectrum {
+            dimensions: dimensions,
+            style: style,
+            bars: Vec::new(),
+            time: None
+        }
+    }
+
+    /// Let the bars follow the spectrum.
+    ///
+    /// # Arguments
+    ///
+    /// * `spectrum` - Lowest and highest level from 0 to 1 of each column.
+    /// * `elapsed` - Seconds since the last update.
+    fn update_bars(&mut self, spectrum: &[(f32, f32)], elapsed: f32) {
+        if self.bars.len() != spectrum.len() {
+            self.bars = vec![Bar::new(); spectrum.len()];
+        }
+        for (bar, &(min, max)) in self.bars.iter_mut().zip(spectrum) {
+            bar.update(min, max, elapsed, &self.style);
+        }
     }
 }
 
@@ -17,13 +106,137 @@ impl Scene for SceneSpectrum {
             renderer: &mut Renderer,
             _: &Info,
             spectrum: &SpectrumResult,
-            _: u64) -> Result<(), String> {
-        let rects = spectrum.spectrum.iter().enumerate().map(|(x, &(_, max))| {
-            let value = max * 15.0;
-            Rect::new(x as i32, 16i32 - value.max(0.0f32) as i32, 1, value as u32)
+            time: u64) -> Result<(), String> {
+        // The time restarts with every scene, in which case the bars just jump to the spectrum
+        let elapsed = match self.time {
+            Some(last) if time >= last => (time - last) as f32 / 1000.0,
+            _ => 0.0
+        };
+        self.time = Some(time);
+        self.update_bars(&spectrum.spectrum, elapsed);
+        let max_height = (self.dimensions.height - 1) as f32;
+        let bottom = self.dimensions.height as i32;
+        let rects = self.bars.iter().enumerate().map(|(x, bar)| {
+            let value = bar.max * max_height;
+            let base = if self.style.show_range { (bar.min * max_height) as i32 } else { 0 };
+            let height = (value as i32 - base).max(0);
+            Rect::new(x as i32, bottom - value.max(0.0f32) as i32, 1, height as u32)
         }).collect::<Vec<Rect>>();
-        renderer.draw_rects(&rects)
+        try!(renderer.draw_rects(&rects));
+        if self.style.show_range {
+            let bases = self.bars.iter().enumerate()
+                .map(|(x, bar)| (x, (bar.min * max_height) as i32))
+                .filter(|&(_, base)| base > 0)
+                .map(|(x, base)| Rect::new(x as i32, bottom - base, 1, base as u32))
+                .collect::<Vec<Rect>>();
+            let color = renderer.draw_color();
+            renderer.set_draw_color(Color::RGBA(0, 0, 0, RANGE_BASE_INTENSITY));
+            try!(renderer.draw_rects(&bases));
+            renderer.set_draw_color(color);
+        }
+        if self.style.peak_hold.is_some() {
+            let peaks = self.bars.iter().enumerate()
+                .filter(|&(_, bar)| (bar.peak * max_height) as i32 > (bar.max * max_height) as i32)
+                .map(|(x, bar)| Point::new(x as i32, bottom - (bar.peak * max_height) as i32))
+                .collect::<Vec<Point>>();
+            try!(renderer.draw_points(&peaks));
+        }
+        Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use mpd::status::State;
+    use spectrum::SpectrumResult;
+    use target::display::read_intensities;
+    use test_helpers::*;
+    use dimensions::Dimensions;
+    use graphics::scene::Scene;
+    use super::*;
+
+    #[test]
+    fn bar_rises_instantly_and_falls_slowly() {
+        let style = SpectrumStyle {
+            fall_rate: 2.0,
+            peak_hold: Some(0.45),
+            peak_fall_rate: 1.0,
+            show_range: false
+        };
+        let mut bar = Bar::new();
+        bar.update(0.0, 0.8, 0.1, &style);
+        assert!((bar.max - 0.8).abs() < 0.001);
+        bar.update(0.0, 0.0, 0.1, &style);
+        assert!((bar.max - 0.6).abs() < 0.001);
+        bar.update(0.0, 0.7, 0.1, &style);
+        assert!((bar.max - 0.7).abs() < 0.001);
+        bar.update(0.0, 0.0, 1.0, &style);
+        assert!(bar.max.abs() < 0.001);
+    }
+
+    #[test]
+    fn peak_is_held() {
+        let style = SpectrumStyle {
+            fall_rate: 2.0,
+            peak_hold: Some(0.45),
+            peak_fall_rate: 1.0,
+            show_range: false
+        };
+        let mut bar = Bar::new();
+        bar.update(0.0, 1.0, 0.1, &style);
+        for _ in 0 .. 4 {
+            bar.update(0.0, 0.0, 0.1, &style);
+            assert!((bar.peak - 1.0).abs() < 0.001);
+        }
+        bar.update(0.0, 0.0, 0.1, &style);
+        assert!((bar.peak - 0.9).abs() < 0.001);
+        // The peak never falls below the bar
+        bar.update(0.0, 0.95, 0.1, &style);
+        assert!((bar.peak - 0.95).abs() < 0.001);
+        assert!(bar.peak_age.abs() < 0.001);
+    }
+
+    #[test]
+    fn range_stays_below_max() {
+        let style = SpectrumStyle {
+            fall_rate: 2.0,
+            peak_hold: Some(0.45),
+            peak_fall_rate: 1.0,
+            show_range: false
+        };
+        let mut bar = Bar::new();
+        bar.update(0.5, 0.6, 0.1, &style);
+        bar.update(0.1, 0.2, 0.1, &style);
+        assert!((bar.max - 0.4).abs() < 0.001);
+        assert!((bar.min - 0.3).abs() < 0.001);
+        bar.update(0.0, 0.0, 0.2, &style);
+        assert!(bar.min <= bar.max);
+    }
 
+    #[test]
+    fn range_above_dimmed_bar() {
+        let mut renderer = create_test_renderer();
+        let dimensions = Dimensions::new(32, 16);
+        let style = SpectrumStyle {
+            fall_rate: 2.0,
+            peak_hold: Some(0.45),
+            peak_fall_rate: 1.0,
+            show_range: true
+        };
+        let mut scene = SceneSpectrum::new(&mut renderer, dimensions, style);
+        let spectrum = SpectrumResult {
+            spectrum: vec![(0.5, 1.0); 32],
+            amplitude: Vec::new(),
+            stereo: None,
+            beat: false,
+            bpm: None
+        };
+        scene.draw(&mut renderer, &create_test_info(true, State::Play, 100), &spectrum, 0).unwrap();
+        let intensities = read_intensities(&renderer, dimensions);
+        assert_eq!(0, intensities[0]);
+        assert_eq!(255, intensities[32]);
+        assert_eq!(255, intensities[8 * 32]);
+        assert_eq!(RANGE_BASE_INTENSITY, intensities[9 * 32]);
+        assert_eq!(RANGE_BASE_INTENSITY, intensities[15 * 32]);
+    }
+}
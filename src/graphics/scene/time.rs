@@ -7,22 +7,33 @@ use spectrum::SpectrumResult;
 use graphics::scene::Scene;
 use graphics::font::FontRenderer;
 use chrono::Timelike;
+use dimensions::Dimensions;
+
+/// Size of the clock with the large font and the second bars around it.
+const FULL_LAYOUT_WIDTH: u32 = 32;
+const FULL_LAYOUT_HEIGHT: u32 = 16;
 
 pub struct SceneTime {
+    dimensions: Dimensions,
+    font_3x5: FontRenderer,
     font_7x12: FontRenderer
 }
 
 impl SceneTime {
-    pub fn new(renderer: &mut Renderer) -> SceneTime {
+    pub fn new(renderer: &mut Renderer, dimensions: Dimensions) -> SceneTime {
+        let font_3x5 = FontRenderer::new(3, 5, renderer.load_texture(Path::new("assets/3x5.png")).unwrap());
         let font_7x12 = FontRenderer::new(7, 12, renderer.load_texture(Path::new("assets/7x12.png")).unwrap());
         SceneTime {
+            dimensions: dimensions,
+            font_3x5: font_3x5,
             font_7x12: font_7x12
         }
     }
-}
 
-impl Scene for SceneTime {
-    fn draw(&mut self, renderer: &mut Renderer, info: &Info, _: &SpectrumResult, _: u64) -> Result<(), String> {
+    /// Hours and minutes in the large font, surrounded by four bars filling up with the seconds.
+    fn draw_full(&self, renderer: &mut Renderer, info: &Info) -> Result<(), String> {
+        let left = (self.dimensions.width - FULL_LAYOUT_WIDTH) as i32 / 2;
+        let top = (self.dimensions.height - FULL_LAYOUT_HEIGHT) as i32 / 2;
         let hours = info.time.format("%H").to_string();
         let minutes = info.time.format("%M").to_string();
         let second = info.time.second() as f32;
@@ -46,9 +57,32 @@ impl Scene for SceneTime {
             let y = (x + 1) % 2;
             Point::new(17 + x, y)
         }));
+        let points = points.iter().map(|point| point.offset(left, top)).collect::<Vec<Point>>();
         try!(renderer.draw_points(&points));
-        try!(self.font_7x12.text(Point::new(0, 0), &hours, renderer));
-        self.font_7x12.text(Point::new(17, 4), &minutes, renderer)
+        try!(self.font_7x12.text(Point::new(left, top), &hours, renderer));
+        self.font_7x12.text(Point::new(left + 17, top + 4), &minutes, renderer)
+    }
+
+    /// Hours and minutes in the small font with a single bar filling up with the seconds below.
+    fn draw_compact(&self, renderer: &mut Renderer, info: &Info) -> Result<(), String> {
+        let text = info.time.format("%H:%M").to_string();
+        let left = (self.dimensions.width as i32 - self.font_3x5.text_width(&text) as i32) / 2;
+        let top = (self.dimensions.height as i32 - self.font_3x5.height as i32 - 2) / 2;
+        try!(self.font_3x5.text(Point::new(left, top), &text, renderer));
+        let bar_width = (self.dimensions.width * info.time.second() / 60) as i32;
+        let points = (0 .. bar_width)
+            .map(|x| Point::new(x, self.dimensions.height as i32 - 1))
+            .collect::<Vec<Point>>();
+        renderer.draw_points(&points)
     }
 }
 
+impl Scene for SceneTime {
+    fn draw(&mut self, renderer: &mut Renderer, info: &Info, _: &SpectrumResult, _: u64) -> Result<(), String> {
+        if self.dimensions.width >= FULL_LAYOUT_WIDTH && self.dimensions.height >= FULL_LAYOUT_HEIGHT {
+            self.draw_full(renderer, info)
+        } else {
+            self.draw_compact(renderer, info)
+        }
+    }
+}
@@ -0,0 +1,446 @@
+use sdl2::render::{Renderer, Texture};
+use sdl2::rect::{Point, Rect};
+use sdl2::pixels::Color;
+use nalgebra::Vector2;
+use dimensions::Dimensions;
+use graphics::assignment::assign;
+
+/// Share of the transition the columns of the rain start falling in, the rest of the time they need
+/// to reach the bottom.
+const RAIN_SPREAD: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down
+}
+
+/// The effects which can be used when switching from one scene to another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionKind {
+    /// Every lit pixel walks towards a lit pixel of the new scene, assigned so that the pixels walk
+    /// as little as possible.
+    Migration,
+    /// The new scene pushes the old one out in the direction.
+    Slide(Direction),
+    /// The new scene is uncovered by an edge moving in the direction.
+    Wipe(Direction),
+    /// The pixels switch to the new scene one by one in random order.
+    Dissolve,
+    /// The columns of the new scene fall down from the top, each starting at a random time.
+    Rain
+}
+
+impl TransitionKind {
+    pub fn parse(name: &str) -> Result<TransitionKind, String> {
+        match name {
+            "migration" => Ok(TransitionKind::Migration),
+            "slide-left" => Ok(TransitionKind::Slide(Direction::Left)),
+            "slide-right" => Ok(TransitionKind::Slide(Direction::Right)),
+            "slide-up" => Ok(TransitionKind::Slide(Direction::Up)),
+            "slide-down" => Ok(TransitionKind::Slide(Direction::Down)),
+            "wipe-left" => Ok(TransitionKind::Wipe(Direction::Left)),
+            "wipe-right" => Ok(TransitionKind::Wipe(Direction::Right)),
+            "wipe-up" => Ok(TransitionKind::Wipe(Direction::Up)),
+            "wipe-down" => Ok(TransitionKind::Wipe(Direction::Down)),
+            "dissolve" => Ok(TransitionKind::Dissolve),
+            "rain" => Ok(TransitionKind::Rain),
+            _ => Err(format!("Unknown transition \"{}\".", name))
+        }
+    }
+}
+
+/// Which transition leads into a scene and how long it takes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransitionConfig {
+    pub kind: TransitionKind,
+    /// Milliseconds the transition takes.
+    pub duration: u64
+}
+
+pub trait Transition {
+    /// Draw the state of the transition onto the current render target.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - Texture containing the scene which is left.
+    /// * `new` - Texture containing the scene which is entered.
+    /// * `progress` - How far the transition is, from 0 (only the old scene) to 1 (only the new scene).
+    fn draw(&mut self,
+            renderer: &mut Renderer,
+            old: &Texture,
+            new: &Texture,
+            progress: f32) -> Result<(), String>;
+}
+
+/// Create the transition between two scenes.
+///
+/// # Arguments
+///
+/// * `kind` - The effect to use.
+/// * `origin` - The lit pixels of the old scene.
+/// * `target` - The lit pixels of the new scene.
+/// * `seed` - Seed for the random patterns of dissolve and rain, so that they differ between
+///            transitions.
+pub fn create(kind: TransitionKind,
+        origin: Vec<Vector2<f32>>,
+        target: Vec<Vector2<f32>>,
+        dimensions: Dimensions,
+        seed: u32) -> Box<Transition> {
+    match kind {
+        TransitionKind::Migration => Box::new(TransitionMigration::new(origin, target)),
+        TransitionKind::Slide(direction) => Box::new(TransitionSlide::new(direction, dimensions)),
+        TransitionKind::Wipe(direction) => Box::new(TransitionWipe::new(direction, dimensions)),
+        TransitionKind::Dissolve => Box::new(TransitionDissolve::new(dimensions, seed)),
+        TransitionKind::Rain => Box::new(TransitionRain::new(dimensions, seed))
+    }
+}
+
+trait ToSdlPoint {
+    fn to_sdl(&self) -> Point;
+}
+
+impl ToSdlPoint for Vector2<f32> {
+    fn to_sdl(&self) -> Point {
+        Point::new(self.x as i32, self.y as i32)
+    }
+}
+
+fn clear(renderer: &mut Renderer) {
+    renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
+    renderer.clear();
+}
+
+/// Copy a part of the texture over whatever was drawn there before, skipping empty parts which SDL
+/// would enlarge to one pixel.
+fn copy_part(renderer: &mut Renderer, texture: &Texture, source: (i32, i32, i32, i32), x: i32, y: i32) -> Result<(), String> {
+    let (source_x, source_y, width, height) = source;
+    if width <= 0 || height <= 0 {
+        return Ok(());
+    }
+    let destination = Rect::new(x, y, width as u32, height as u32);
+    // The texture is blended, so its unlit pixels would leave the lit ones below visible
+    renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
+    try!(renderer.fill_rect(destination));
+    renderer.copy(texture, Some(Rect::new(source_x, source_y, width as u32, height as u32)), Some(destination))
+}
+
+/// Pseudo random numbers, so that no dependency is needed for shuffling a few pixels.
+struct Random {
+    state: u32
+}
+
+impl Random {
+    fn new(seed: u32) -> Random {
+        // Spread similar seeds apart, as xorshift needs a while to turn them into different numbers
+        Random {
+            state: seed.wrapping_mul(2_654_435_761).max(1)
+        }
+    }
+
+    /// The next number from 0 to 1.
+    fn next(&mut self) -> f32 {
+        // Xorshift
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state % 1_000_000) as f32 / 1_000_000.0
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1 .. items.len()).rev() {
+            let other = ((self.next() * (index + 1) as f32) as usize).min(index);
+            items.swap(index, other);
+        }
+    }
+}
+
+pub struct TransitionMigration {
+    /// Each pixel of the old scene along with the pixel of the new scene it walks to.
+    pairs: Vec<(Vector2<f32>, Vector2<f32>)>,
+    /// Steps the longest walk takes.
+    steps: u32
+}
+
+impl TransitionMigration {
+    pub fn new(origin: Vec<Vector2<f32>>, target: Vec<Vector2<f32>>) -> TransitionMigration {
+        let pairs = assign(&origin, &target);
+        let steps = pairs.iter()
+            .map(|&(origin, target)| ((target.x - origin.x).abs() + (target.y - origin.y).abs()) as u32)
+            .max()
+            .unwrap_or(0);
+        TransitionMigration {
+            pairs: pairs,
+            steps: steps
+        }
+    }
+
+    fn approach(a: f32, b: f32) -> f32 {
+        if (a - b).abs() < ::std::f32::EPSILON {
+            a
+        } else if a > b {
+            a - 1.0
+        } else {
+            a + 1.0
+        }
+    }
+
+    /// Position of a pixel after walking the specified amount of steps towards its target, moving
+    /// alternately horizontally and vertically.
+    fn position(origin: Vector2<f32>, target: Vector2<f32>, steps: u32) -> Vector2<f32> {
+        let mut position = origin;
+        for step in 0 .. steps {
+            let x = TransitionMigration::approach(position.x, target.x);
+            let y = TransitionMigration::approach(position.y, target.y);
+            if (x - position.x).abs() > ::std::f32::EPSILON && (y - position.y).abs() > ::std::f32::EPSILON {
+                if step % 2 == 1 {
+                    position.y = y;
+                } else {
+                    position.x = x;
+                }
+            } else {
+                position = Vector2::new(x, y);
+            }
+        }
+        position
+    }
+}
+
+impl Transition for TransitionMigration {
+    fn draw(&mut self, renderer: &mut Renderer, _: &Texture, _: &Texture, progress: f32) -> Result<(), String> {
+        let steps = (progress * self.steps as f32).round() as u32;
+        let points = self.pairs.iter()
+            .map(|&(origin, target)| TransitionMigration::position(origin, target, steps).to_sdl())
+            .collect::<Vec<Point>>();
+        clear(renderer);
+        renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
+        renderer.draw_points(&points)
+    }
+}
+
+pub struct TransitionSlide {
+    direction: Direction,
+    dimensions: Dimensions
+}
+
+impl TransitionSlide {
+    pub fn new(direction: Direction, dimensions: Dimensions) -> TransitionSlide {
+        TransitionSlide {
+            direction: direction,
+            dimensions: dimensions
+        }
+    }
+}
+
+impl Transition for TransitionSlide {
+    fn draw(&mut self, renderer: &mut Renderer, old: &Texture, new: &Texture, progress: f32) -> Result<(), String> {
+        let (width, height) = (self.dimensions.width as i32, self.dimensions.height as i32);
+        let horizontal = (progress * width as f32).round() as i32;
+        let vertical = (progress * height as f32).round() as i32;
+        // Offset of the old scene, the new one follows directly behind it
+        let (x, y, follow_x, follow_y) = match self.direction {
+            Direction::Left => (-horizontal, 0, width, 0),
+            Direction::Right => (horizontal, 0, -width, 0),
+            Direction::Up => (0, -vertical, 0, height),
+            Direction::Down => (0, vertical, 0, -height)
+        };
+        clear(renderer);
+        try!(renderer.copy(old, None, Some(Rect::new(x, y, width as u32, height as u32))));
+        renderer.copy(new, None, Some(Rect::new(x + follow_x, y + follow_y, width as u32, height as u32)))
+    }
+}
+
+pub struct TransitionWipe {
+    direction: Direction,
+    dimensions: Dimensions
+}
+
+impl TransitionWipe {
+    pub fn new(direction: Direction, dimensions: Dimensions) -> TransitionWipe {
+        TransitionWipe {
+            direction: direction,
+            dimensions: dimensions
+        }
+    }
+}
+
+impl Transition for TransitionWipe {
+    fn draw(&mut self, renderer: &mut Renderer, old: &Texture, new: &Texture, progress: f32) -> Result<(), String> {
+        let (width, height) = (self.dimensions.width as i32, self.dimensions.height as i32);
+        let horizontal = (progress * width as f32).round() as i32;
+        let vertical = (progress * height as f32).round() as i32;
+        // The uncovered part of the new scene, which is shown at the same position
+        let part = match self.direction {
+            Direction::Left => (width - horizontal, 0, horizontal, height),
+            Direction::Right => (0, 0, horizontal, height),
+            Direction::Up => (0, height - vertical, width, vertical),
+            Direction::Down => (0, 0, width, vertical)
+        };
+        clear(renderer);
+        try!(renderer.copy(old, None, Some(self.dimensions.rect())));
+        copy_part(renderer, new, part, part.0, part.1)
+    }
+}
+
+pub struct TransitionDissolve {
+    /// All pixels in the order they switch to the new scene.
+    order: Vec<(i32, i32)>,
+    dimensions: Dimensions
+}
+
+impl TransitionDissolve {
+    pub fn new(dimensions: Dimensions, seed: u32) -> TransitionDissolve {
+        let mut order = (0 .. dimensions.height as i32)
+            .flat_map(|y| (0 .. dimensions.width as i32).map(move |x| (x, y)))
+            .collect::<Vec<(i32, i32)>>();
+        Random::new(seed).shuffle(&mut order);
+        TransitionDissolve {
+            order: order,
+            dimensions: dimensions
+        }
+    }
+}
+
+impl Transition for TransitionDissolve {
+    fn draw(&mut self, renderer: &mut Renderer, old: &Texture, new: &Texture, progress: f32) -> Result<(), String> {
+        let switched = (progress * self.order.len() as f32).round() as usize;
+        clear(renderer);
+        try!(renderer.copy(old, None, Some(self.dimensions.rect())));
+        for &(x, y) in self.order.iter().take(switched) {
+            try!(copy_part(renderer, new, (x, y, 1, 1), x, y));
+        }
+        Ok(())
+    }
+}
+
+pub struct TransitionRain {
+    /// Progress at which each column starts falling.
+    delays: Vec<f32>,
+    dimensions: Dimensions
+}
+
+impl TransitionRain {
+    pub fn new(dimensions: Dimensions, seed: u32) -> TransitionRain {
+        let mut random = Random::new(seed);
+        TransitionRain {
+            delays: (0 .. dimensions.width).map(|_| random.next() * RAIN_SPREAD).collect(),
+            dimensions: dimensions
+        }
+    }
+
+    /// Amount of rows of each column the new scene has fallen down.
+    fn fallen(&self, progress: f32) -> Vec<i32> {
+        let height = self.dimensions.height as f32;
+        self.delays.iter().map(|delay| {
+            let column_progress = ((progress - delay) / (1.0 - RAIN_SPREAD)).max(0.0).min(1.0);
+            (column_progress * height).round() as i32
+        }).collect()
+    }
+}
+
+impl Transition for TransitionRain {
+    fn draw(&mut self, renderer: &mut Renderer, old: &Texture, new: &Texture, progress: f32) -> Result<(), String> {
+        let height = self.dimensions.height as i32;
+        clear(renderer);
+        try!(renderer.copy(old, None, Some(self.dimensions.rect())));
+        for (x, fallen) in self.fallen(progress).into_iter().enumerate() {
+            // The bottom of the new column is shown at the top, covering the old one
+            try!(copy_part(renderer, new, (x as i32, height - fallen, 1, fallen), x as i32, 0));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use sdl2::pixels::PixelFormatEnum;
+    use dimensions::Dimensions;
+    use target::display::read_intensities;
+    use test_helpers::*;
+    use super::*;
+
+    /// A texture of the size of the test renderer in which only the specified pixels are lit.
+    fn create_test_texture(renderer: &mut Renderer, on: &[(i32, i32)]) -> Texture {
+        let texture = renderer.create_texture_target(PixelFormatEnum::RGBA8888, 32, 16).unwrap();
+        renderer.render_target().unwrap().set(texture).unwrap();
+        renderer.set_draw_color(Color::RGBA(255, 255, 255, 0));
+        renderer.clear();
+        renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
+        for &(x, y) in on {
+            renderer.draw_point(Point::new(x, y)).unwrap();
+        }
+        renderer.render_target().unwrap().reset().unwrap().unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(TransitionKind::Slide(Direction::Up), TransitionKind::parse("slide-up").unwrap());
+        assert_eq!(TransitionKind::Rain, TransitionKind::parse("rain").unwrap());
+        assert!(TransitionKind::parse("fade-to-grey").is_err());
+    }
+
+    #[test]
+    fn approach() {
+        assert!(TransitionMigration::approach(5.0, 9.0) - 6.0 < ::std::f32::EPSILON);
+        assert!(TransitionMigration::approach(7.0, 3.0) - 6.0 < ::std::f32::EPSILON);
+        assert!(TransitionMigration::approach(6.0, 6.0) - 6.0 < ::std::f32::EPSILON);
+    }
+
+    #[test]
+    fn migration_position() {
+        let origin = Vector2::new(0.0, 0.0);
+        let target = Vector2::new(2.0, 4.0);
+        assert_eq!(origin, TransitionMigration::position(origin, target, 0));
+        assert_eq!(Vector2::new(1.0, 1.0), TransitionMigration::position(origin, target, 2));
+        assert_eq!(Vector2::new(2.0, 3.0), TransitionMigration::position(origin, target, 5));
+        assert_eq!(target, TransitionMigration::position(origin, target, 6));
+        assert_eq!(target, TransitionMigration::position(origin, target, 10));
+        let migration = TransitionMigration::new(vec![origin], vec![target]);
+        assert_eq!(6, migration.steps);
+    }
+
+    #[test]
+    fn dissolve_covers_every_pixel() {
+        let mut order = TransitionDissolve::new(Dimensions::new(32, 16), 1000).order;
+        assert_ne!((0 .. 32).map(|x| (x, 0)).collect::<Vec<_>>(), order[.. 32].to_vec());
+        assert_ne!(TransitionDissolve::new(Dimensions::new(32, 16), 1017).order, order);
+        order.sort();
+        order.dedup();
+        assert_eq!(32 * 16, order.len());
+    }
+
+    #[test]
+    fn rain_falls() {
+        let rain = TransitionRain::new(Dimensions::new(32, 16), 1000);
+        assert!(rain.fallen(0.0).iter().all(|&fallen| fallen == 0));
+        assert!(rain.fallen(1.0).iter().all(|&fallen| fallen == 16));
+        let halfway = rain.fallen(0.5);
+        assert!(halfway.iter().any(|&fallen| fallen != halfway[0]));
+    }
+
+    #[test]
+    fn covered_pixels_are_off() {
+        let dimensions = Dimensions::new(32, 16);
+        let mut renderer = create_test_renderer();
+        let old = create_test_texture(&mut renderer, &[(3, 2)]);
+        let new = create_test_texture(&mut renderer, &[(20, 10)]);
+        let mut transitions = vec![
+            create(TransitionKind::Wipe(Direction::Right), vec![], vec![], dimensions, 1000),
+            create(TransitionKind::Dissolve, vec![], vec![], dimensions, 1000),
+            create(TransitionKind::Rain, vec![], vec![], dimensions, 1000)
+        ];
+        for transition in transitions.iter_mut() {
+            transition.draw(&mut renderer, &old, &new, 0.0).unwrap();
+            let intensities = read_intensities(&renderer, dimensions);
+            assert_eq!(0xFF, intensities[2 * 32 + 3]);
+            assert_eq!(0, intensities[10 * 32 + 20]);
+            transition.draw(&mut renderer, &old, &new, 1.0).unwrap();
+            let intensities = read_intensities(&renderer, dimensions);
+            assert_eq!(0, intensities[2 * 32 + 3]);
+            assert_eq!(0xFF, intensities[10 * 32 + 20]);
+        }
+    }
+}
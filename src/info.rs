@@ -1,30 +1,111 @@
 use chrono::{DateTime, Local, Duration};
-use std::time::{Instant};
-use mpd::Client;
+use std::time::{Instant, Duration as StdDuration};
+use mpd::{Client, Idle, Subsystem};
+use mpd::error::Error as MpdError;
 use mpd::status::State;
-use std::sync::mpsc::{SyncSender, SendError};
+use std::sync::mpsc::{channel, Receiver, Sender, SyncSender, TryRecvError};
 use bus::{BusReader};
 use ControlStatus;
 use std::thread;
+use std::env;
+
+const DEFAULT_MPD_HOST: &'static str = "127.0.0.1";
+const DEFAULT_MPD_PORT: u16 = 6600;
+const RECONNECT_DELAY_MIN: u64 = 500;
+const RECONNECT_DELAY_MAX: u64 = 30_000;
+const DISCONNECTED_POLL_INTERVAL: u64 = 100;
+const WATCHED_SUBSYSTEMS: [Subsystem; 3] = [Subsystem::Player, Subsystem::Mixer, Subsystem::Options];
+
+/// Everything needed to connect to the MPD server the information is read from.
+#[derive(Clone)]
+pub struct MpdConfig {
+    pub host: String,
+    pub port: u16,
+    pub password: Option<String>
+}
+
+impl MpdConfig {
+    /// Create a new configuration from the specified values, falling back to the environment
+    /// variables `MPD_HOST` and `MPD_PORT` and finally to MPD's defaults.
+    ///
+    /// Like with `mpc`, the host may be given as `password@host`. An explicitly specified password
+    /// takes precedence over one embedded into the host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Hostname or address of the MPD server, optionally prefixed by `password@`.
+    /// * `port` - Port the MPD server is listening on.
+    /// * `password` - Password to login with after connecting.
+    pub fn new(host: Option<&str>, port: Option<&str>, password: Option<&str>) -> Result<MpdConfig, String> {
+        let host = host.map(String::from)
+            .or_else(|| env::var("MPD_HOST").ok())
+            .unwrap_or_else(|| String::from(DEFAULT_MPD_HOST));
+        let (host_password, host) = match host.rfind('@') {
+            Some(index) => (Some(String::from(&host[.. index])), String::from(&host[index + 1 ..])),
+            None => (None, host)
+        };
+        let port = match port.map(String::from).or_else(|| env::var("MPD_PORT").ok()) {
+            Some(port) => try!(port.parse::<u16>().map_err(|_| format!("Invalid MPD port \"{}\".", port))),
+            None => DEFAULT_MPD_PORT
+        };
+        Ok(MpdConfig {
+            host: host,
+            port: port,
+            password: password.map(String::from).or(host_password)
+        })
+    }
+}
 
 #[derive(Clone)]
 pub struct Info {
+    pub connected: bool,
     pub volume: i8,
-    pub ms: u64,
     pub time: DateTime<Local>,
     pub artist: String,
     pub song: String,
+    /// Id of the current song in the queue of MPD, which differs between entries even if their tags
+    /// are the same.
+    pub song_id: Option<u32>,
     pub duration: Duration,
     pub elapsed: Duration,
     pub state: State
 }
 
-fn get_render_info(mpd: &mut Client, start_time: Instant) -> Info {
-    let elapsed = Instant::now().duration_since(start_time);
-    let ms = (1_000_000_000 * elapsed.as_secs() + elapsed.subsec_nanos() as u64)/(1_000_000) + 2000;
-    let actual_time: DateTime<Local> = Local::now();
-    let status = mpd.status().unwrap();
-    let optional_song = mpd.currentsong().unwrap();
+/// The state of MPD as of the last time it was queried.
+struct MpdSnapshot {
+    received: Instant,
+    volume: i8,
+    artist: String,
+    song: String,
+    song_id: Option<u32>,
+    duration: Duration,
+    elapsed: Duration,
+    state: State
+}
+
+impl MpdSnapshot {
+    /// Estimate the current playback position from the time that passed since the snapshot was taken.
+    fn estimate_elapsed(&self) -> Duration {
+        if self.state != State::Play {
+            return self.elapsed;
+        }
+        let since = Duration::milliseconds(to_ms(Instant::now().duration_since(self.received)) as i64);
+        let elapsed = self.elapsed + since;
+        if self.duration > Duration::zero() && elapsed > self.duration {
+            self.duration
+        } else {
+            elapsed
+        }
+    }
+}
+
+fn to_ms(duration: StdDuration) -> u64 {
+    (1_000_000_000 * duration.as_secs() + duration.subsec_nanos() as u64) / 1_000_000
+}
+
+fn get_snapshot(mpd: &mut Client) -> Result<MpdSnapshot, MpdError> {
+    let status = try!(mpd.status());
+    let optional_song = try!(mpd.currentsong());
     let (title, artist) = if optional_song.is_some() {
         let song = optional_song.unwrap();
         (song.title.unwrap_or_else(|| String::from("")), song.tags.get("Artist").unwrap_or(&String::from("")).clone())
@@ -32,31 +113,215 @@ fn get_render_info(mpd: &mut Client, start_time: Instant) -> Info {
         (String::from(""), String::from(""))
     };
     let (elapsed, duration) = status.time.unwrap_or((Duration::seconds(0), Duration::seconds(0)));
-    Info {
+    Ok(MpdSnapshot {
+        received: Instant::now(),
         volume: status.volume,
-        ms: ms,
-        time: actual_time,
-        song: title,
         artist: artist,
+        song: title,
+        song_id: status.song.map(|place| place.id.0),
         duration: duration,
-        elapsed: elapsed,
+        elapsed: status.elapsed.unwrap_or(elapsed),
         state: status.state
+    })
+}
+
+fn get_render_info(snapshot: &MpdSnapshot) -> Info {
+    Info {
+        connected: true,
+        volume: snapshot.volume,
+        time: Local::now(),
+        song: snapshot.song.clone(),
+        artist: snapshot.artist.clone(),
+        song_id: snapshot.song_id,
+        duration: snapshot.duration,
+        elapsed: snapshot.estimate_elapsed(),
+        state: snapshot.state
+    }
+}
+
+fn get_disconnected_info() -> Info {
+    Info {
+        connected: false,
+        volume: 0,
+        time: Local::now(),
+        song: String::from(""),
+        artist: String::from(""),
+        song_id: None,
+        duration: Duration::seconds(0),
+        elapsed: Duration::seconds(0),
+        state: State::Stop
+    }
+}
+
+/// Connect to the MPD server, returning `None` if the server could not be reached.
+///
+/// Failing to login is not considered recoverable, as retrying with the same password won't help.
+fn connect(config: &MpdConfig) -> Result<Option<Client>, String> {
+    let mut mpd = match Client::connect((config.host.as_str(), config.port)) {
+        Ok(mpd) => mpd,
+        Err(err) => {
+            println!("Unable to connect to MPD at {}:{}: {}", config.host, config.port, err);
+            return Ok(None);
+        }
+    };
+    if let Some(ref password) = config.password {
+        try!(mpd.login(password).map_err(|err| format!("Unable to login to MPD: {}", err)));
+    }
+    Ok(Some(mpd))
+}
+
+/// Whether the error means that the connection to MPD is unusable, rather than that the server
+/// rejected a single command.
+fn is_connection_error(err: &MpdError) -> bool {
+    match *err {
+        MpdError::Io(_) | MpdError::Parse(_) => true,
+        _ => false
     }
 }
 
-pub fn run(mut control_rx: BusReader<ControlStatus>, sender: SyncSender<Info>) -> Result<(), SendError<Info>> {
-    let mut mpd = Client::connect("127.0.0.1:6600").unwrap();
-    let start_time = Instant::now();
+/// Query MPD once and then every time MPD reports a change in one of the watched subsystems
+/// using the `idle` command.
+///
+/// All errors are reported through the channel. Stops, closing the connection, after the first
+/// error which breaks the connection, if the initial state could not be queried or once the
+/// receiving end hung up.
+fn watch(mut mpd: Client, sender: Sender<Result<MpdSnapshot, MpdError>>) {
+    let mut initial = true;
     loop {
-        let result = sender.send(get_render_info(&mut mpd, start_time));
-        if !result.is_ok() {
-            return result;
+        let snapshot = get_snapshot(&mut mpd);
+        let failed = match snapshot {
+            Err(ref err) => initial || is_connection_error(err),
+            Ok(_) => false
+        };
+        initial = false;
+        if sender.send(snapshot).is_err() || failed {
+            return;
+        }
+        if let Err(err) = mpd.wait(&WATCHED_SUBSYSTEMS) {
+            let failed = is_connection_error(&err);
+            if sender.send(Err(err)).is_err() || failed {
+                return;
+            }
+        }
+    }
+}
+
+pub fn run(mut control_rx: BusReader<ControlStatus>, sender: SyncSender<Info>, config: MpdConfig) -> Result<(), String> {
+    let mut snapshot_rx: Option<Receiver<Result<MpdSnapshot, MpdError>>> = None;
+    let mut snapshot: Option<MpdSnapshot> = None;
+    let mut reconnect_delay = RECONNECT_DELAY_MIN;
+    let mut next_reconnect = Instant::now();
+    loop {
+        if snapshot_rx.is_none() && Instant::now() >= next_reconnect {
+            if let Some(mpd) = try!(connect(&config)) {
+                let (snapshot_tx, receiver) = channel();
+                thread::spawn(move || watch(mpd, snapshot_tx));
+                // Block until the initial state arrived to not report a disconnect in between
+                snapshot = match receiver.recv() {
+                    Ok(Ok(initial)) => Some(initial),
+                    Ok(Err(err)) => {
+                        println!("Unable to query MPD: {}", err);
+                        None
+                    },
+                    Err(_) => None
+                };
+                if snapshot.is_some() {
+                    reconnect_delay = RECONNECT_DELAY_MIN;
+                }
+                snapshot_rx = Some(receiver);
+            } else {
+                next_reconnect = Instant::now() + StdDuration::from_millis(reconnect_delay);
+                reconnect_delay = (reconnect_delay * 2).min(RECONNECT_DELAY_MAX);
+            }
+        }
+        let mut lost = snapshot_rx.is_some() && snapshot.is_none();
+        if let Some(ref receiver) = snapshot_rx {
+            loop {
+                match receiver.try_recv() {
+                    Ok(Ok(update)) => snapshot = Some(update),
+                    Ok(Err(err)) => {
+                        if is_connection_error(&err) {
+                            println!("Lost connection to MPD: {}", err);
+                            lost = true;
+                        } else {
+                            println!("MPD reported an error: {}", err);
+                        }
+                    },
+                    Err(TryRecvError::Disconnected) => lost = true,
+                    Err(TryRecvError::Empty) => break
+                }
+                if lost {
+                    break;
+                }
+            }
         }
+        if lost {
+            snapshot_rx = None;
+            snapshot = None;
+            next_reconnect = Instant::now() + StdDuration::from_millis(reconnect_delay);
+            reconnect_delay = (reconnect_delay * 2).min(RECONNECT_DELAY_MAX);
+        }
+        let info = match snapshot {
+            Some(ref snapshot) => get_render_info(snapshot),
+            None => get_disconnected_info()
+        };
+        // Blocks until the renderer picks up the info, which limits the rate to the frame rate
+        try!(sender.send(info).map_err(|_| String::from("Render thread hung up.")));
         if let Ok(status) = control_rx.try_recv() {
             if status == ControlStatus::Abort {
                 return Ok(())
             }
         }
-        thread::yield_now();
+        if snapshot_rx.is_none() {
+            thread::sleep(StdDuration::from_millis(DISCONNECTED_POLL_INTERVAL));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use mpd::error::{Error as MpdError, ErrorCode, ServerError};
+    use super::*;
+
+    #[test]
+    fn mpd_config_host() {
+        let config = MpdConfig::new(Some("music.local"), Some("6601"), None).unwrap();
+        assert_eq!("music.local", config.host);
+        assert_eq!(6601, config.port);
+        assert_eq!(None, config.password);
+    }
+
+    #[test]
+    fn mpd_config_password_in_host() {
+        let config = MpdConfig::new(Some("secret@music.local"), Some("6600"), None).unwrap();
+        assert_eq!("music.local", config.host);
+        assert_eq!(Some(String::from("secret")), config.password);
+        let config = MpdConfig::new(Some("secret@music.local"), Some("6600"), Some("other")).unwrap();
+        assert_eq!(Some(String::from("other")), config.password);
+    }
+
+    #[test]
+    fn mpd_config_password_containing_at() {
+        let config = MpdConfig::new(Some("se@cret@music.local"), Some("6600"), None).unwrap();
+        assert_eq!("music.local", config.host);
+        assert_eq!(Some(String::from("se@cret")), config.password);
+    }
+
+    #[test]
+    fn mpd_config_invalid_port() {
+        assert!(MpdConfig::new(Some("music.local"), Some("66000"), None).is_err());
+        assert!(MpdConfig::new(Some("music.local"), Some("mpd"), None).is_err());
+    }
+
+    #[test]
+    fn connection_errors() {
+        assert!(is_connection_error(&MpdError::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))));
+        assert!(!is_connection_error(&MpdError::Server(ServerError {
+            code: ErrorCode::Permission,
+            pos: 0,
+            command: String::from("status"),
+            detail: String::from("you don't have permission for \"status\"")
+        })));
     }
 }
@@ -12,6 +12,7 @@ extern crate pulse_simple;
 extern crate dft;
 extern crate bus;
 extern crate nalgebra;
+extern crate yaml_rust;
 extern crate core;
 
 mod graphics;
@@ -19,19 +20,38 @@ mod info;
 mod spectrum;
 mod display;
 mod target;
+mod dimensions;
+mod gif;
+mod network;
+mod receiver;
 
 #[cfg(test)]
 mod test_helpers;
 
 use bus::{Bus, BusReader};
-use target::Target;
-use target::display::TargetDisplay;
-use target::window::TargetWindow;
+use target::TargetConfig;
+use target::display::DisplayConfig;
+use target::frames::{FramesConfig, FramesOutput};
+use target::terminal::{TerminalConfig, TerminalStyle};
+use target::network::NetworkConfig;
+use network::Protocol;
+use receiver::ReceiverConfig;
+use spectrum::SpectrumConfig;
+use spectrum::bands::{BandConfig, BandLayout};
+use spectrum::level::{AutoGainConfig, DecibelRange};
+use spectrum::source::SourceConfig;
+use spectrum::window::WindowFunction;
+use graphics::{GraphicsConfig, SpectrumStyle};
+use graphics::playlist::Playlist;
+use target::brightness::{self, BrightnessSchedule};
 use std::thread::{spawn, JoinHandle};
 use std::sync::mpsc::{sync_channel, channel, Receiver, SyncSender, Sender};
-use clap::{App};
+use clap::{App, ArgMatches};
 use spectrum::SpectrumResult;
-use info::Info;
+use info::{Info, MpdConfig};
+use dimensions::Dimensions;
+use display::mapping::{DeviceMapping, Orientation};
+use std::process::exit;
 
 #[derive(Clone, PartialEq)]
 pub enum ControlStatus {
@@ -43,25 +63,34 @@ fn thread_render(
         control_rx: BusReader<ControlStatus>,
         info_rx: Receiver<Info>,
         spectrum_rx: Receiver<SpectrumResult>,
-        use_display: bool) -> JoinHandle<()> {
+        target_config: TargetConfig,
+        graphics_config: GraphicsConfig,
+        dimensions: Dimensions) -> JoinHandle<()> {
     spawn(move || {
-        let mut target: Box<Target> = if use_display {
-            Box::new(TargetDisplay::new(info_rx, spectrum_rx).unwrap())
-        } else {
-            Box::new(TargetWindow::new(info_rx, spectrum_rx).unwrap())
+        let mut target = match target::create(target_config, info_rx, spectrum_rx, dimensions) {
+            Ok(target) => target,
+            Err(err) => {
+                println!("Unable to initialize render target: {}", err);
+                control_tx.send(ControlStatus::Abort).ok();
+                return;
+            }
         };
-        if !target.run(control_rx).is_ok() {
-            control_tx.send(ControlStatus::Abort).ok();
+        if let Err(err) = target.run(control_rx, graphics_config) {
+            println!("Error in render thread: {}", err);
         }
+        // Stop the other threads as well once the target is done
+        control_tx.send(ControlStatus::Abort).ok();
     })
 }
 
 fn thread_spectrum(
         control_tx: SyncSender<ControlStatus>,
         control_rx: BusReader<ControlStatus>,
-        spectrum_tx: Sender<SpectrumResult>) -> JoinHandle<()> {
+        spectrum_tx: Sender<SpectrumResult>,
+        spectrum_config: SpectrumConfig) -> JoinHandle<()> {
     spawn(move || {
-        if !spectrum::run(control_rx, spectrum_tx).is_ok() {
+        if let Err(err) = spectrum::run(control_rx, spectrum_tx, spectrum_config) {
+            println!("Error in spectrum thread: {}", err);
             control_tx.send(ControlStatus::Abort).ok();
         }
     })
@@ -70,25 +99,238 @@ fn thread_spectrum(
 fn thread_info(
         control_tx: SyncSender<ControlStatus>,
         control_rx: BusReader<ControlStatus>,
-        info_tx: SyncSender<Info>) -> JoinHandle<()> {
+        info_tx: SyncSender<Info>,
+        mpd_config: MpdConfig) -> JoinHandle<()> {
     spawn(move || {
-        if !info::run(control_rx, info_tx).is_ok() {
+        if let Err(err) = info::run(control_rx, info_tx, mpd_config) {
+            println!("Error in info thread: {}", err);
             control_tx.send(ControlStatus::Abort).ok();
         }
     })
 }
 
+/// Print the error and exit if the result is not ok.
+fn unwrap_or_exit<T>(result: Result<T, String>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => {
+            println!("{}", err);
+            exit(1);
+        }
+    }
+}
+
+/// Build the table mapping the devices in the chain onto the frame from the commandline.
+fn device_mapping(arguments: &ArgMatches, dimensions: Dimensions) -> Result<Vec<DeviceMapping>, String> {
+    let devices_horizontal = dimensions.width as usize / 8;
+    let devices_vertical = dimensions.height as usize / 8;
+    let mapping = match arguments.value_of("device-map") {
+        Some(table) => try!(DeviceMapping::parse_table(table)),
+        None => try!(DeviceMapping::layout(arguments.value_of("chain").unwrap(), devices_horizontal, devices_vertical))
+    };
+    let orientation = try!(Orientation::parse(arguments.value_of("orientation").unwrap_or("")));
+    Ok(mapping.into_iter()
+        .map(|device| DeviceMapping::new(device.x, device.y, orientation.then(device.orientation)))
+        .collect())
+}
+
+/// Build the brightness schedule from the commandline.
+fn brightness_schedule(arguments: &ArgMatches) -> Result<BrightnessSchedule, String> {
+    let day = try!(value_t!(arguments, "day-brightness", u8).map_err(|err| err.to_string()));
+    let night = if arguments.is_present("night-brightness") {
+        try!(value_t!(arguments, "night-brightness", u8).map_err(|err| err.to_string()))
+    } else {
+        day
+    };
+    let stopped = if arguments.is_present("stopped-brightness") {
+        Some(try!(value_t!(arguments, "stopped-brightness", u8).map_err(|err| err.to_string())))
+    } else {
+        None
+    };
+    Ok(BrightnessSchedule {
+        day: day,
+        night: night,
+        day_start: try!(brightness::parse_time(arguments.value_of("day-start").unwrap())),
+        night_start: try!(brightness::parse_time(arguments.value_of("night-start").unwrap())),
+        fade: try!(value_t!(arguments, "brightness-fade", u32).map_err(|err| err.to_string())),
+        stopped: stopped,
+        follow_volume: arguments.is_present("dim-with-volume")
+    })
+}
+
+/// Build the settings for the audio source selected on the commandline.
+fn source_config(arguments: &ArgMatches) -> Result<SourceConfig, String> {
+    let device = arguments.value_of("audio-device").map(String::from);
+    let sample_rate = try!(value_t!(arguments, "sample-rate", u32).map_err(|err| err.to_string()));
+    Ok(match arguments.value_of("audio-source").unwrap() {
+        "fifo" => SourceConfig::Fifo {
+            path: device.unwrap_or_else(|| String::from("/tmp/mpd.fifo")),
+            sample_rate: sample_rate
+        },
+        "alsa" => SourceConfig::Alsa {
+            device: device.unwrap_or_else(|| String::from("hw:Loopback,1")),
+            sample_rate: sample_rate
+        },
+        "wav" => SourceConfig::Wav(try!(device.ok_or_else(|| String::from("The WAV file needs to be specified using --audio-device.")))),
+        _ => SourceConfig::Pulse {
+            device: device,
+            sample_rate: sample_rate
+        }
+    })
+}
+
+/// Build the settings for analysing the audio from the commandline.
+fn spectrum_config(arguments: &ArgMatches, dimensions: Dimensions) -> Result<SpectrumConfig, String> {
+    let layout = try!(BandLayout::parse(arguments.value_of("bands").unwrap()));
+    let low = try!(value_t!(arguments, "low-cutoff", f32).map_err(|err| err.to_string()));
+    let high = try!(value_t!(arguments, "high-cutoff", f32).map_err(|err| err.to_string()));
+    let hop = if arguments.is_present("hop") {
+        Some(try!(value_t!(arguments, "hop", usize).map_err(|err| err.to_string())))
+    } else {
+        None
+    };
+    let floor = try!(value_t!(arguments, "floor", f32).map_err(|err| err.to_string()));
+    let ceiling = try!(value_t!(arguments, "ceiling", f32).map_err(|err| err.to_string()));
+    let auto_gain = if arguments.is_present("fixed-gain") {
+        None
+    } else {
+        Some(AutoGainConfig {
+            attack: try!(value_t!(arguments, "attack", f32).map_err(|err| err.to_string())) / 1000.0,
+            release: try!(value_t!(arguments, "release", f32).map_err(|err| err.to_string())) / 1000.0,
+            max_gain: try!(value_t!(arguments, "max-gain", f32).map_err(|err| err.to_string()))
+        })
+    };
+    Ok(SpectrumConfig {
+        columns: dimensions.width as usize,
+        source: try!(source_config(arguments)),
+        bands: try!(BandConfig::new(layout, low, high)),
+        window: try!(WindowFunction::parse(arguments.value_of("window-function").unwrap())),
+        hop: hop,
+        levels: try!(DecibelRange::new(floor, ceiling)),
+        auto_gain: auto_gain,
+        stereo: arguments.is_present("stereo")
+    })
+}
+
+/// Build the settings for drawing the scenes from the commandline.
+fn graphics_config(arguments: &ArgMatches) -> Result<GraphicsConfig, String> {
+    let peak_hold = if arguments.is_present("no-peaks") {
+        None
+    } else {
+        Some(try!(value_t!(arguments, "peak-hold", f32).map_err(|err| err.to_string())) / 1000.0)
+    };
+    let announcement = if arguments.is_present("no-announcement") {
+        None
+    } else {
+        Some(try!(value_t!(arguments, "announcement", u64).map_err(|err| err.to_string())))
+    };
+    let playlist = match arguments.value_of("playlist") {
+        Some(path) => try!(Playlist::load(path)),
+        None => Playlist::default()
+    };
+    Ok(GraphicsConfig {
+        playlist: playlist,
+        announcement: announcement,
+        spectrum: SpectrumStyle {
+            fall_rate: try!(value_t!(arguments, "fall-rate", f32).map_err(|err| err.to_string())),
+            peak_hold: peak_hold,
+            peak_fall_rate: try!(value_t!(arguments, "peak-fall-rate", f32).map_err(|err| err.to_string())),
+            show_range: arguments.is_present("show-range")
+        }
+    })
+}
+
+/// Build the settings for the target selected on the commandline.
+fn target_config(arguments: &ArgMatches, dimensions: Dimensions) -> Result<TargetConfig, String> {
+    if arguments.is_present("window") {
+        return Ok(TargetConfig::Window);
+    }
+    if let Some(style) = arguments.value_of("terminal") {
+        return Ok(TargetConfig::Terminal(TerminalConfig {
+            style: if style == "braille" { TerminalStyle::Braille } else { TerminalStyle::HalfBlock },
+            fps: try!(value_t!(arguments, "terminal-fps", u32).map_err(|err| err.to_string()))
+        }));
+    }
+    let frames_output = match (arguments.value_of("frames"), arguments.value_of("gif")) {
+        (Some(directory), _) => Some(FramesOutput::Png(String::from(directory))),
+        (_, Some(path)) => Some(FramesOutput::Gif(String::from(path))),
+        _ => None
+    };
+    if let Some(output) = frames_output {
+        return Ok(TargetConfig::Frames(FramesConfig {
+            output: output,
+            interval: if arguments.is_present("frame-interval") {
+                Some(try!(value_t!(arguments, "frame-interval", u32).map_err(|err| err.to_string())))
+            } else {
+                None
+            },
+            count: if arguments.is_present("frame-count") {
+                Some(try!(value_t!(arguments, "frame-count", u32).map_err(|err| err.to_string())))
+            } else {
+                None
+            }
+        }));
+    }
+    if let Some(address) = arguments.value_of("send") {
+        return Ok(TargetConfig::Network(NetworkConfig {
+            protocol: try!(Protocol::parse(arguments.value_of("protocol").unwrap())),
+            address: String::from(address),
+            brightness: try!(brightness_schedule(arguments)),
+            dither: arguments.is_present("dither")
+        }));
+    }
+    Ok(TargetConfig::Display(try!(display_config(arguments, dimensions))))
+}
+
+/// Read the size of the canvas, which is made up of whole devices of 8x8 pixels.
+fn dimensions(arguments: &ArgMatches) -> Result<Dimensions, String> {
+    let width = try!(value_t!(arguments, "width", u32).map_err(|err| err.to_string()));
+    let height = try!(value_t!(arguments, "height", u32).map_err(|err| err.to_string()));
+    if width == 0 || width % 8 != 0 || height == 0 || height % 8 != 0 {
+        return Err(format!("The width and height need to be positive multiples of 8, not {}x{}.", width, height));
+    }
+    Ok(Dimensions::new(width, height))
+}
+
+/// Build the settings for driving the dotmatrix from the commandline.
+fn display_config(arguments: &ArgMatches, dimensions: Dimensions) -> Result<DisplayConfig, String> {
+    Ok(DisplayConfig {
+        mapping: try!(device_mapping(arguments, dimensions)),
+        output: arguments.value_of("display-file").map(String::from),
+        spi_device: String::from(arguments.value_of("spi-device").unwrap()),
+        spi_speed: try!(value_t!(arguments, "spi-speed", u32).map_err(|err| err.to_string())),
+        brightness: try!(brightness_schedule(arguments)),
+        dither: arguments.is_present("dither")
+    })
+}
+
 fn main() {
     let yaml = load_yaml!("commandline.yml");
     let arguments = App::from_yaml(yaml).get_matches();
-    let use_display = !arguments.is_present("window");
+    let dimensions = unwrap_or_exit(dimensions(&arguments));
+    if let Some(address) = arguments.value_of("receive") {
+        let receiver_config = ReceiverConfig {
+            protocol: unwrap_or_exit(Protocol::parse(arguments.value_of("protocol").unwrap())),
+            address: String::from(address),
+            display: unwrap_or_exit(display_config(&arguments, dimensions))
+        };
+        unwrap_or_exit(receiver::run(dimensions, receiver_config));
+        return;
+    }
+    let mpd_config = unwrap_or_exit(MpdConfig::new(
+        arguments.value_of("mpd-host"),
+        arguments.value_of("mpd-port"),
+        arguments.value_of("mpd-password")));
+    let target_config = unwrap_or_exit(target_config(&arguments, dimensions));
+    let spectrum_config = unwrap_or_exit(spectrum_config(&arguments, dimensions));
+    let graphics_config = unwrap_or_exit(graphics_config(&arguments));
     let (info_tx, info_rx) = sync_channel(0);
     let (spectrum_tx, spectrum_rx) = channel();
     let (control_tx, control_rx) = sync_channel(3);
     let mut control_bus = Bus::new(3);
-    let join_render = thread_render(control_tx.clone(), control_bus.add_rx(), info_rx, spectrum_rx, use_display);
-    let join_info = thread_info(control_tx.clone(), control_bus.add_rx(), info_tx);
-    let join_spectrum = thread_spectrum(control_tx.clone(), control_bus.add_rx(), spectrum_tx);
+    let join_render = thread_render(control_tx.clone(), control_bus.add_rx(), info_rx, spectrum_rx, target_config, graphics_config, dimensions);
+    let join_info = thread_info(control_tx.clone(), control_bus.add_rx(), info_tx, mpd_config);
+    let join_spectrum = thread_spectrum(control_tx.clone(), control_bus.add_rx(), spectrum_tx, spectrum_config);
     let join_control = spawn(move || {
         for message in control_rx.iter() {
             control_bus.broadcast(message.clone());
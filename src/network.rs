@@ -0,0 +1,291 @@
+use std::io::{self, Error, ErrorKind};
+use std::io::prelude::*;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, SystemTime};
+
+const MAGIC: [u8; 2] = [b'M', b'P'];
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 12;
+/// Largest packet fitting into a single UDP datagram.
+const MAX_PACKET_SIZE: usize = 65507;
+/// How long to wait before trying to connect again after a TCP connection failed.
+const RECONNECT_INTERVAL: u64 = 1000;
+/// How long connecting via TCP may block the render loop, enough for a receiver in the local network.
+const CONNECT_TIMEOUT: u64 = 10;
+/// How long sending a frame via TCP may block the render loop.
+const WRITE_TIMEOUT: u64 = 100;
+
+/// Transport protocol used to stream the frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    /// Each frame is sent as one datagram, lost frames are simply skipped.
+    Udp,
+    /// Frames are sent through one connection, each prefixed with its length.
+    Tcp
+}
+
+impl Protocol {
+    pub fn parse(name: &str) -> Result<Protocol, String> {
+        match name {
+            "udp" => Ok(Protocol::Udp),
+            "tcp" => Ok(Protocol::Tcp),
+            _ => Err(format!("Unknown protocol \"{}\".", name))
+        }
+    }
+}
+
+/// One frame as it is sent over the network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Increases with every frame sent, so that receivers can drop reordered datagrams.
+    pub sequence: u32,
+    /// Intensity the display should be set to.
+    pub brightness: u8,
+    pub width: u16,
+    pub height: u16,
+    /// Whether each pixel is on, row by row.
+    pub pixels: Vec<bool>
+}
+
+fn write_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.push((value >> 8) as u8);
+    bytes.push(value as u8);
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    (bytes[0] as u16) << 8 | bytes[1] as u16
+}
+
+impl Frame {
+    /// Encode the frame into a packet, consisting of a fixed size header followed by the pixels
+    /// packed into bits, most significant bit first.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(HEADER_SIZE + (self.pixels.len() + 7) / 8);
+        packet.extend_from_slice(&MAGIC);
+        packet.push(VERSION);
+        packet.push(self.brightness);
+        write_u16(&mut packet, (self.sequence >> 16) as u16);
+        write_u16(&mut packet, self.sequence as u16);
+        write_u16(&mut packet, self.width);
+        write_u16(&mut packet, self.height);
+        for byte in self.pixels.chunks(8) {
+            packet.push(byte.iter().enumerate().fold(0, |data, (bit, &on)| {
+                if on { data | 0x80 >> bit } else { data }
+            }));
+        }
+        packet
+    }
+
+    /// Decode a packet created by `encode`.
+    pub fn decode(packet: &[u8]) -> Result<Frame, String> {
+        if packet.len() < HEADER_SIZE || packet[0 .. 2] != MAGIC {
+            return Err(String::from("Packet is not a frame."));
+        }
+        if packet[2] != VERSION {
+            return Err(format!("Unsupported version {} of the packet.", packet[2]));
+        }
+        let width = read_u16(&packet[8 .. 10]);
+        let height = read_u16(&packet[10 .. 12]);
+        let pixels = width as usize * height as usize;
+        let data = &packet[HEADER_SIZE ..];
+        if data.len() != (pixels + 7) / 8 {
+            return Err(format!("Packet contains {} bytes of pixels, expected {} for {}x{}.", data.len(), (pixels + 7) / 8, width, height));
+        }
+        Ok(Frame {
+            sequence: (read_u16(&packet[4 .. 6]) as u32) << 16 | read_u16(&packet[6 .. 8]) as u32,
+            brightness: packet[3],
+            width: width,
+            height: height,
+            pixels: (0 .. pixels).map(|index| data[index / 8] & 0x80 >> (index % 8) != 0).collect()
+        })
+    }
+}
+
+fn resolve(address: &str) -> io::Result<SocketAddr> {
+    try!(address.to_socket_addrs()).next()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unable to resolve \"{}\".", address)))
+}
+
+/// Sending end of a stream of frames.
+pub enum Connection {
+    Udp {
+        socket: UdpSocket,
+        address: SocketAddr
+    },
+    Tcp {
+        address: SocketAddr,
+        stream: Option<TcpStream>,
+        last_attempt: Option<SystemTime>
+    }
+}
+
+impl Connection {
+    /// Prepare sending frames to the specified address. TCP connections are established lazily
+    /// and reestablished whenever they break.
+    pub fn open(protocol: Protocol, address: &str) -> Result<Connection, String> {
+        match protocol {
+            Protocol::Udp => {
+                let address = try!(resolve(address).map_err(|err| err.to_string()));
+                let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = try!(UdpSocket::bind(local).map_err(|err| format!("Unable to bind UDP socket: {}", err)));
+                Ok(Connection::Udp {
+                    socket: socket,
+                    address: address
+                })
+            },
+            Protocol::Tcp => Ok(Connection::Tcp {
+                address: try!(resolve(address).map_err(|err| err.to_string())),
+                stream: None,
+                last_attempt: None
+            })
+        }
+    }
+
+    /// Send one packet. Errors are reported, but never fatal, as the receiver might come back later.
+    pub fn send(&mut self, packet: &[u8]) {
+        match *self {
+            Connection::Udp { ref socket, address } => {
+                // Datagrams get lost anyway, a receiver which is not running yet is no different
+                socket.send_to(packet, address).ok();
+            },
+            Connection::Tcp { address, ref mut stream, ref mut last_attempt } => {
+                if stream.is_none() {
+                    let now = SystemTime::now();
+                    let due = last_attempt.map(|last_attempt| {
+                        now.duration_since(last_attempt).map(|elapsed| elapsed >= Duration::from_millis(RECONNECT_INTERVAL)).unwrap_or(true)
+                    }).unwrap_or(true);
+                    if !due {
+                        return;
+                    }
+                    let reported = last_attempt.is_some();
+                    *last_attempt = Some(now);
+                    match TcpStream::connect_timeout(&address, Duration::from_millis(CONNECT_TIMEOUT)) {
+                        Ok(connected) => {
+                            connected.set_nodelay(true).ok();
+                            connected.set_write_timeout(Some(Duration::from_millis(WRITE_TIMEOUT))).ok();
+                            *stream = Some(connected);
+                        },
+                        Err(err) => {
+                            if !reported {
+                                println!("Unable to connect to {}, retrying: {}", address, err);
+                            }
+                            return;
+                        }
+                    }
+                }
+                let mut data = Vec::with_capacity(packet.len() + 2);
+                write_u16(&mut data, packet.len() as u16);
+                data.extend_from_slice(packet);
+                if let Err(err) = stream.as_mut().unwrap().write_all(&data) {
+                    println!("Lost connection to {}, reconnecting: {}", address, err);
+                    // The loss is already reported, so failed attempts to reconnect are not
+                    *stream = None;
+                    *last_attempt = Some(SystemTime::now());
+                }
+            }
+        }
+    }
+}
+
+/// Receiving end of a stream of frames.
+pub enum Listener {
+    Udp(UdpSocket),
+    Tcp {
+        listener: TcpListener,
+        stream: Option<TcpStream>
+    }
+}
+
+impl Listener {
+    /// Listen for frames on the specified address.
+    pub fn bind(protocol: Protocol, address: &str) -> io::Result<Listener> {
+        match protocol {
+            Protocol::Udp => Ok(Listener::Udp(try!(UdpSocket::bind(address)))),
+            Protocol::Tcp => Ok(Listener::Tcp {
+                listener: try!(TcpListener::bind(address)),
+                stream: None
+            })
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match *self {
+            Listener::Udp(ref socket) => socket.local_addr(),
+            Listener::Tcp { ref listener, .. } => listener.local_addr()
+        }
+    }
+
+    /// Block until the next packet arrives. With TCP only one sender is served at a time, the
+    /// next one is accepted once its connection is closed.
+    pub fn receive(&mut self) -> io::Result<Vec<u8>> {
+        match *self {
+            Listener::Udp(ref socket) => {
+                let mut buffer = vec![0u8; MAX_PACKET_SIZE];
+                let (length, _) = try!(socket.recv_from(&mut buffer));
+                buffer.truncate(length);
+                Ok(buffer)
+            },
+            Listener::Tcp { ref listener, ref mut stream } => loop {
+                if stream.is_none() {
+                    let (accepted, _) = try!(listener.accept());
+                    *stream = Some(accepted);
+                }
+                let result = {
+                    let stream = stream.as_mut().unwrap();
+                    let mut length = [0u8; 2];
+                    stream.read_exact(&mut length).and_then(|_| {
+                        let mut packet = vec![0u8; read_u16(&length) as usize];
+                        stream.read_exact(&mut packet).map(|_| packet)
+                    })
+                };
+                match result {
+                    Ok(packet) => return Ok(packet),
+                    Err(_) => *stream = None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_helpers::*;
+    use super::*;
+
+    const PATTERN: &'static [(usize, usize)] = &[(0, 0), (3, 0), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2), (3, 2)];
+
+    #[test]
+    fn encode() {
+        assert_eq!(vec![b'M', b'P', 1, 7, 0x01, 0x02, 0x03, 0x04, 0, 4, 0, 3, 0x96, 0xF0], create_test_frame(0x01020304, 7, 4, 3, PATTERN).encode());
+    }
+
+    #[test]
+    fn decode() {
+        let frame = create_test_frame(0xFFFFFFFE, 7, 4, 3, PATTERN);
+        assert_eq!(Ok(frame.clone()), Frame::decode(&frame.encode()));
+        let mut packet = frame.encode();
+        packet.pop();
+        assert!(Frame::decode(&packet).is_err());
+        assert!(Frame::decode(b"GIF89a").is_err());
+    }
+
+    fn loopback(protocol: Protocol) {
+        let mut listener = Listener::bind(protocol, "127.0.0.1:0").unwrap();
+        let mut connection = Connection::open(protocol, &listener.local_addr().unwrap().to_string()).unwrap();
+        for sequence in 0 .. 3 {
+            let frame = create_test_frame(sequence, 7, 4, 3, PATTERN);
+            connection.send(&frame.encode());
+            assert_eq!(Ok(frame), Frame::decode(&listener.receive().unwrap()));
+        }
+    }
+
+    #[test]
+    fn loopback_udp() {
+        loopback(Protocol::Udp);
+    }
+
+    #[test]
+    fn loopback_tcp() {
+        loopback(Protocol::Tcp);
+    }
+}
@@ -0,0 +1,126 @@
+use display::Display;
+use display::transport::Transport;
+use network::{Frame, Listener, Protocol};
+use target::display::{open_display, DisplayConfig};
+use dimensions::Dimensions;
+
+/// Amount of frames a frame may arrive late before it is considered to be from a restarted sender.
+const SEQUENCE_WINDOW: u32 = 60;
+
+/// Settings for showing frames received over the network on the dotmatrix.
+pub struct ReceiverConfig {
+    pub protocol: Protocol,
+    /// Address to listen on, for example `0.0.0.0:7219`.
+    pub address: String,
+    /// The dotmatrix to show the frames on. Brightness and dithering are up to the sender.
+    pub display: DisplayConfig
+}
+
+/// Shows the received frames on the display, skipping duplicated and reordered ones.
+struct FrameDisplay<T: Transport> {
+    display: Display<T>,
+    dimensions: Dimensions,
+    sequence: Option<u32>,
+    brightness: Option<u8>
+}
+
+impl<T: Transport> FrameDisplay<T> {
+    fn new(display: Display<T>, dimensions: Dimensions) -> FrameDisplay<T> {
+        FrameDisplay {
+            display: display,
+            dimensions: dimensions,
+            sequence: None,
+            brightness: None
+        }
+    }
+
+    /// Show the frame if it is newer than the last one shown.
+    fn show(&mut self, frame: &Frame) -> Result<(), String> {
+        if let Some(sequence) = self.sequence {
+            if sequence.wrapping_sub(frame.sequence) < SEQUENCE_WINDOW {
+                return Ok(());
+            }
+        }
+        if frame.width as u32 != self.dimensions.width || frame.height as u32 != self.dimensions.height {
+            return Err(format!("Received a frame of {}x{}, but the display is {}x{}.",
+                frame.width,
+                frame.height,
+                self.dimensions.width,
+                self.dimensions.height));
+        }
+        self.sequence = Some(frame.sequence);
+        if self.brightness != Some(frame.brightness) {
+            try!(self.display.set_intensity(frame.brightness).map_err(|err| err.to_string()));
+            self.brightness = Some(frame.brightness);
+        }
+        self.display.display(&frame.pixels).map_err(|err| err.to_string())
+    }
+}
+
+/// Receive frames and show them on the dotmatrix until an unrecoverable error occurs.
+pub fn run(dimensions: Dimensions, config: ReceiverConfig) -> Result<(), String> {
+    let display = try!(open_display(dimensions, &config.display));
+    let mut frame_display = FrameDisplay::new(display, dimensions);
+    let mut listener = try!(Listener::bind(config.protocol, &config.address)
+        .map_err(|err| format!("Unable to listen on {}: {}", config.address, err)));
+    if let Ok(address) = listener.local_addr() {
+        println!("Receiving frames on {}", address);
+    }
+    loop {
+        let packet = try!(listener.receive().map_err(|err| format!("Unable to receive frame: {}", err)));
+        let result = Frame::decode(&packet).and_then(|frame| frame_display.show(&frame));
+        if let Err(err) = result {
+            println!("Skipping frame: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use display::Display;
+    use display::mapping::DeviceMapping;
+    use display::transport::Recorder;
+    use dimensions::Dimensions;
+    use test_helpers::*;
+    use std::mem::replace;
+
+    /// Take the transfers recorded since the last call.
+    fn transfers(frame_display: &mut FrameDisplay<Recorder>) -> Vec<Vec<u8>> {
+        replace(&mut frame_display.display.transport_mut().transfers, Vec::new())
+    }
+
+    #[test]
+    fn show() {
+        let display = Display::new(Recorder::new(), 1, 1, DeviceMapping::row_major(1, 1)).unwrap();
+        let mut frame_display = FrameDisplay::new(display, Dimensions::new(8, 8));
+        frame_display.display.clear().unwrap();
+        transfers(&mut frame_display);
+        frame_display.show(&create_test_frame(1, 3, 8, 8, &[(1, 0)])).unwrap();
+        assert_eq!(vec![vec![0xA, 3], vec![8, 0x02]], transfers(&mut frame_display));
+        frame_display.show(&create_test_frame(2, 3, 8, 8, &[(2, 0)])).unwrap();
+        assert_eq!(vec![vec![8, 0x04]], transfers(&mut frame_display));
+    }
+
+    #[test]
+    fn show_reordered() {
+        let display = Display::new(Recorder::new(), 1, 1, DeviceMapping::row_major(1, 1)).unwrap();
+        let mut frame_display = FrameDisplay::new(display, Dimensions::new(8, 8));
+        frame_display.display.clear().unwrap();
+        frame_display.show(&create_test_frame(5, 1, 8, 8, &[(5, 0)])).unwrap();
+        transfers(&mut frame_display);
+        frame_display.show(&create_test_frame(4, 1, 8, 8, &[(4, 0)])).unwrap();
+        frame_display.show(&create_test_frame(5, 1, 8, 8, &[(4, 0)])).unwrap();
+        assert!(transfers(&mut frame_display).is_empty());
+        frame_display.show(&create_test_frame(5 + SEQUENCE_WINDOW, 1, 8, 8, &[(4, 0)])).unwrap();
+        assert!(!transfers(&mut frame_display).is_empty());
+    }
+
+    #[test]
+    fn show_wrong_dimensions() {
+        let display = Display::new(Recorder::new(), 1, 1, DeviceMapping::row_major(1, 1)).unwrap();
+        let mut frame_display = FrameDisplay::new(display, Dimensions::new(8, 8));
+        frame_display.display.clear().unwrap();
+        assert!(frame_display.show(&create_test_frame(0, 1, 16, 8, &[])).is_err());
+    }
+}
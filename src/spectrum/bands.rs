@@ -0,0 +1,189 @@
+use std::ops::Range;
+
+/// Frequency the octave bands are centered around, as defined by IEC 61260.
+const REFERENCE_FREQUENCY: f32 = 1000.0;
+
+/// How the frequencies are distributed onto the columns of the spectrum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BandLayout {
+    /// Each column covers the same range of frequencies.
+    Linear,
+    /// Each column covers the same ratio of frequencies.
+    Logarithmic,
+    /// Standard octave bands, each spread over several columns.
+    Octave,
+    /// Standard third-octave bands, each spread over one or more columns.
+    ThirdOctave,
+    /// Each column covers the same range of pitch as perceived by the human ear.
+    Mel
+}
+
+impl BandLayout {
+    pub fn parse(name: &str) -> Result<BandLayout, String> {
+        match name {
+            "linear" => Ok(BandLayout::Linear),
+            "logarithmic" => Ok(BandLayout::Logarithmic),
+            "octave" => Ok(BandLayout::Octave),
+            "third-octave" => Ok(BandLayout::ThirdOctave),
+            "mel" => Ok(BandLayout::Mel),
+            _ => Err(format!("Unknown band layout \"{}\".", name))
+        }
+    }
+}
+
+fn to_mel(frequency: f32) -> f32 {
+    2595.0 * (1.0 + frequency / 700.0).log10()
+}
+
+fn from_mel(mel: f32) -> f32 {
+    700.0 * (10.0f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Which frequencies are shown in the spectrum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BandConfig {
+    layout: BandLayout,
+    low: f32,
+    high: f32
+}
+
+impl BandConfig {
+    /// # Arguments
+    ///
+    /// * `layout` - How the frequencies are distributed onto the columns.
+    /// * `low` - Lowest frequency shown in Hz.
+    /// * `high` - Highest frequency shown in Hz.
+    pub fn new(layout: BandLayout, low: f32, high: f32) -> Result<BandConfig, String> {
+        if low < 0.0 || high <= low {
+            return Err(format!("Invalid frequency range from {} Hz to {} Hz.", low, high));
+        }
+        if low == 0.0 && layout != BandLayout::Linear && layout != BandLayout::Mel {
+            return Err(String::from("The low cutoff frequency needs to be above 0 Hz for logarithmic layouts."));
+        }
+        Ok(BandConfig {
+            layout: layout,
+            low: low,
+            high: high
+        })
+    }
+
+    /// Lower and upper edges of the standard bands with their center between the cutoffs.
+    ///
+    /// # Arguments
+    ///
+    /// * `bands_per_octave` - 1 for octave bands, 3 for third-octave bands.
+    fn octave_bands(&self, bands_per_octave: f32) -> Vec<(f32, f32)> {
+        let first = (bands_per_octave * (self.low / REFERENCE_FREQUENCY).log2()).ceil() as i32;
+        let last = (bands_per_octave * (self.high / REFERENCE_FREQUENCY).log2()).floor() as i32;
+        let half_band = 2.0f32.powf(0.5 / bands_per_octave);
+        let bands = (first .. last + 1).map(|band| {
+            let center = REFERENCE_FREQUENCY * 2.0f32.powf(band as f32 / bands_per_octave);
+            (center / half_band, center * half_band)
+        }).collect::<Vec<_>>();
+        if bands.is_empty() {
+            vec![(self.low, self.high)]
+        } else {
+            bands
+        }
+    }
+
+    /// The range of frequencies in Hz shown in each column.
+    pub fn frequencies(&self, columns: usize) -> Vec<(f32, f32)> {
+        let (low, high) = (self.low, self.high);
+        let edge = |column: usize| {
+            let position = column as f32 / columns as f32;
+            match self.layout {
+                BandLayout::Logarithmic => low * (high / low).powf(position),
+                BandLayout::Mel => from_mel(to_mel(low) + (to_mel(high) - to_mel(low)) * position),
+                _ => low + (high - low) * position
+            }
+        };
+        match self.layout {
+            BandLayout::Octave | BandLayout::ThirdOctave => {
+                let bands = self.octave_bands(if self.layout == BandLayout::Octave { 1.0 } else { 3.0 });
+                (0 .. columns).map(|column| bands[column * bands.len() / columns]).collect()
+            },
+            _ => (0 .. columns).map(|column| (edge(column), edge(column + 1))).collect()
+        }
+    }
+
+    /// The range of bins of the DFT which make up each column, each containing at least one bin.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - Amount of columns in the spectrum.
+    /// * `sample_rate` - Sample rate of the analysed audio.
+    /// * `window_size` - Amount of samples transformed at once.
+    pub fn bins(&self, columns: usize, sample_rate: u32, window_size: usize) -> Vec<Range<usize>> {
+        let last_bin = window_size / 2;
+        let bin = |frequency: f32| ((frequency * window_size as f32 / sample_rate as f32) as usize).min(last_bin);
+        self.frequencies(columns).into_iter().map(|(low, high)| {
+            let start = bin(low).min(last_bin - 1);
+            start .. bin(high).max(start + 1)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f32, actual: f32) {
+        assert!((expected - actual).abs() < expected * 0.001, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn frequencies_linear() {
+        let bands = BandConfig::new(BandLayout::Linear, 0.0, 3000.0).unwrap();
+        let frequencies = bands.frequencies(3);
+        assert_close(1000.0, frequencies[0].1);
+        assert_close(2000.0, frequencies[2].0);
+        assert_close(3000.0, frequencies[2].1);
+    }
+
+    #[test]
+    fn frequencies_logarithmic() {
+        let bands = BandConfig::new(BandLayout::Logarithmic, 20.0, 20000.0).unwrap();
+        let frequencies = bands.frequencies(3);
+        assert_close(20.0, frequencies[0].0);
+        assert_close(200.0, frequencies[1].0);
+        assert_close(2000.0, frequencies[2].0);
+        assert_close(20000.0, frequencies[2].1);
+    }
+
+    #[test]
+    fn frequencies_mel() {
+        let bands = BandConfig::new(BandLayout::Mel, 0.0, 8000.0).unwrap();
+        let frequencies = bands.frequencies(4);
+        assert_close(8000.0, frequencies[3].1);
+        let widths = frequencies.iter().map(|&(low, high)| high - low).collect::<Vec<_>>();
+        assert!(widths.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn frequencies_octave() {
+        let bands = BandConfig::new(BandLayout::Octave, 20.0, 20000.0).unwrap();
+        let frequencies = bands.frequencies(20);
+        assert_close(31.25 / 2.0f32.sqrt(), frequencies[0].0);
+        assert_eq!(frequencies[0], frequencies[1]);
+        assert_close(16000.0 * 2.0f32.sqrt(), frequencies[19].1);
+        let third_octave = BandConfig::new(BandLayout::ThirdOctave, 20.0, 20000.0).unwrap();
+        assert_close(1000.0 * 2.0f32.powf(1.0 / 6.0), third_octave.frequencies(29)[16].1);
+    }
+
+    #[test]
+    fn bins() {
+        let bands = BandConfig::new(BandLayout::Logarithmic, 20.0, 20000.0).unwrap();
+        let bins = bands.bins(32, 48000, 2048);
+        assert_eq!(32, bins.len());
+        assert!(bins.iter().all(|range| range.end > range.start && range.end <= 1024));
+        assert_eq!(0, bins[0].start);
+        assert_eq!(853, bins[31].end);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(BandConfig::new(BandLayout::Linear, 100.0, 100.0).is_err());
+        assert!(BandConfig::new(BandLayout::Logarithmic, 0.0, 100.0).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+/// Seconds of spectral flux the adaptive threshold is calculated from.
+const THRESHOLD_WINDOW: f32 = 1.0;
+/// Amount of standard deviations the flux needs to exceed its mean by to be an onset.
+const THRESHOLD_DEVIATIONS: f32 = 1.5;
+/// Flux below which nothing is considered an onset, so that noise during silence is ignored.
+const MIN_FLUX: f32 = 0.1;
+/// Shortest time in seconds between two onsets.
+const MIN_INTERVAL: f32 = 0.1;
+/// Seconds of onsets the tempo is estimated from.
+const TEMPO_WINDOW: f32 = 8.0;
+/// Tempo estimates are folded into the octave starting at this tempo.
+const MIN_BPM: f32 = 80.0;
+/// Amount of onsets required before the tempo is estimated.
+const MIN_ONSETS: usize = 4;
+
+/// Compress the magnitudes, so that loud frequencies do not dominate the flux.
+fn compress(magnitude: f32) -> f32 {
+    (1.0 + 100.0 * magnitude).ln()
+}
+
+/// Fold a tempo into the octave from `MIN_BPM` to twice `MIN_BPM`.
+fn fold(bpm: f32) -> f32 {
+    let mut bpm = bpm;
+    while bpm < MIN_BPM {
+        bpm *= 2.0;
+    }
+    while bpm >= 2.0 * MIN_BPM {
+        bpm /= 2.0;
+    }
+    bpm
+}
+
+/// Detects onsets by the increase of energy over all frequencies, the spectral flux, and
+/// estimates the tempo from the intervals between them.
+#[derive(Default)]
+pub struct BeatDetector {
+    /// Compressed magnitudes of the previous analysis.
+    previous: Option<Vec<f32>>,
+    /// Recent flux along with the time it occured.
+    flux: VecDeque<(f32, f32)>,
+    /// Times of the recent onsets.
+    onsets: VecDeque<f32>,
+    /// Seconds of audio analysed so far.
+    time: f32
+}
+
+impl BeatDetector {
+    pub fn new() -> BeatDetector {
+        BeatDetector {
+            previous: None,
+            flux: VecDeque::new(),
+            onsets: VecDeque::new(),
+            time: 0.0
+        }
+    }
+
+    /// Analyse the next magnitudes and return whether they contain an onset.
+    ///
+    /// # Arguments
+    ///
+    /// * `magnitudes` - Magnitude of each frequency.
+    /// * `elapsed` - Seconds of audio since the last update.
+    pub fn update(&mut self, magnitudes: &[f32], elapsed: f32) -> bool {
+        self.time += elapsed;
+        let current = magnitudes.iter().cloned().map(compress).collect::<Vec<f32>>();
+        let flux = match self.previous {
+            Some(ref previous) => current.iter()
+                .zip(previous)
+                .map(|(current, previous)| (current - previous).max(0.0))
+                .sum::<f32>() / current.len() as f32,
+            None => 0.0
+        };
+        self.previous = Some(current);
+        let (mean, deviation) = {
+            let count = self.flux.len().max(1) as f32;
+            let mean = self.flux.iter().map(|&(_, flux)| flux).sum::<f32>() / count;
+            let variance = self.flux.iter().map(|&(_, flux)| (flux - mean) * (flux - mean)).sum::<f32>() / count;
+            (mean, variance.sqrt())
+        };
+        let time = self.time;
+        self.flux.push_back((time, flux));
+        while self.flux.front().map(|&(flux_time, _)| time - flux_time > THRESHOLD_WINDOW).unwrap_or(false) {
+            self.flux.pop_front();
+        }
+        let since_onset = self.onsets.back().map(|&onset| time - onset).unwrap_or(MIN_INTERVAL);
+        // The threshold is unreliable until enough flux has been collected
+        let warmed_up = time >= THRESHOLD_WINDOW / 2.0;
+        let onset = warmed_up && flux > MIN_FLUX && flux > mean + THRESHOLD_DEVIATIONS * deviation && since_onset >= MIN_INTERVAL;
+        if onset {
+            self.onsets.push_back(time);
+        }
+        while self.onsets.front().map(|&onset| time - onset > TEMPO_WINDOW).unwrap_or(false) {
+            self.onsets.pop_front();
+        }
+        onset
+    }
+
+    /// Estimate the tempo in beats per minute from the recent onsets, `None` if there are too few.
+    ///
+    /// The intervals between all pairs of onsets vote for a tempo, after folding it into one octave
+    /// so that skipped beats and offbeats still support the same tempo.
+    pub fn bpm(&self) -> Option<f32> {
+        if self.onsets.len() < MIN_ONSETS {
+            return None;
+        }
+        let intervals = self.onsets.iter().enumerate().flat_map(|(index, first)| {
+            self.onsets.iter().skip(index + 1).map(move |second| second - first)
+        }).collect::<Vec<f32>>();
+        // One bin per beat per minute in the octave above MIN_BPM
+        let mut votes = vec![0.0f32; MIN_BPM as usize];
+        for interval in &intervals {
+            let bin = (fold(60.0 / interval) - MIN_BPM) as usize;
+            votes[bin] += 1.0;
+        }
+        // Smooth the votes, so that a tempo between two bins is not split up
+        let smoothed = (0 .. votes.len()).map(|bin| {
+            votes[bin] + 0.5 * (votes[(bin + 1) % votes.len()] + votes[(bin + votes.len() - 1) % votes.len()])
+        }).collect::<Vec<f32>>();
+        let (bin, _) = smoothed.iter().enumerate().fold((0, 0.0), |best, (bin, &vote)| {
+            if vote > best.1 { (bin, vote) } else { best }
+        });
+        // Refine the tempo by averaging the intervals which voted for it
+        let center = MIN_BPM + bin as f32 + 0.5;
+        let matching = intervals.iter()
+            .map(|interval| fold(60.0 / interval))
+            .filter(|bpm| (bpm - center).abs() <= 1.5)
+            .collect::<Vec<f32>>();
+        if matching.is_empty() {
+            None
+        } else {
+            Some(matching.iter().sum::<f32>() / matching.len() as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    /// Feed frames with a burst of energy at the specified beats per minute and return the times of
+    /// the detected onsets.
+    fn feed(detector: &mut BeatDetector, bpm: f32, seconds: f32) -> Vec<f32> {
+        let frames = (seconds / FRAME) as usize;
+        let interval = (60.0 / bpm / FRAME).round() as usize;
+        (0 .. frames).filter(|frame| {
+            let level = match frame % interval {
+                0 => 1.0,
+                1 => 0.5,
+                2 => 0.2,
+                _ => 0.01 * ((frame * 7) % 5) as f32
+            };
+            detector.update(&vec![level; 64], FRAME)
+        }).map(|frame| frame as f32 * FRAME).collect()
+    }
+
+    #[test]
+    fn onsets() {
+        let mut detector = BeatDetector::new();
+        let onsets = feed(&mut detector, 120.0, 4.0);
+        assert_eq!(7, onsets.len());
+        assert!(onsets.windows(2).all(|pair| (pair[1] - pair[0] - 0.5).abs() < 0.01));
+    }
+
+    #[test]
+    fn silence() {
+        let mut detector = BeatDetector::new();
+        assert!((0 .. 300).all(|_| !detector.update(&[0.0; 64], FRAME)));
+        assert_eq!(None, detector.bpm());
+    }
+
+    #[test]
+    fn bpm() {
+        for &(bpm, expected) in &[(120.0, 120.0), (90.0, 90.0), (150.0, 150.0), (60.0, 120.0)] {
+            let mut detector = BeatDetector::new();
+            feed(&mut detector, bpm, 8.0);
+            let estimate = detector.bpm().unwrap();
+            assert!((estimate - expected).abs() < 2.0, "expected {} BPM, estimated {}", expected, estimate);
+        }
+    }
+
+    #[test]
+    fn fold() {
+        assert!((super::fold(60.0) - 120.0).abs() < 0.001);
+        assert!((super::fold(240.0) - 120.0).abs() < 0.001);
+        assert!((super::fold(100.0) - 100.0).abs() < 0.001);
+    }
+}
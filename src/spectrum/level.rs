@@ -0,0 +1,154 @@
+/// Level everything quieter is considered silent, to keep silence from becoming negative infinity.
+const SILENCE: f32 = -120.0;
+
+/// Convert a magnitude relative to full scale into dB.
+pub fn to_decibel(magnitude: f32) -> f32 {
+    if magnitude > 0.0 {
+        (20.0 * magnitude.log10()).max(SILENCE)
+    } else {
+        SILENCE
+    }
+}
+
+/// Convert dB into a factor.
+pub fn from_decibel(decibel: f32) -> f32 {
+    10.0f32.powf(decibel / 20.0)
+}
+
+/// The levels shown on the display, from empty to full height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecibelRange {
+    floor: f32,
+    ceiling: f32
+}
+
+impl DecibelRange {
+    /// # Arguments
+    ///
+    /// * `floor` - Level in dB shown as empty.
+    /// * `ceiling` - Level in dB shown at full height.
+    pub fn new(floor: f32, ceiling: f32) -> Result<DecibelRange, String> {
+        if floor >= ceiling {
+            return Err(format!("The floor of {} dB needs to be below the ceiling of {} dB.", floor, ceiling));
+        }
+        Ok(DecibelRange {
+            floor: floor,
+            ceiling: ceiling
+        })
+    }
+
+    pub fn ceiling(&self) -> f32 {
+        self.ceiling
+    }
+
+    /// Map a level in dB onto the range from 0 (floor) to 1 (ceiling).
+    pub fn scale(&self, decibel: f32) -> f32 {
+        ((decibel - self.floor) / (self.ceiling - self.floor)).max(0.0).min(1.0)
+    }
+}
+
+/// How quickly the automatic gain follows the music.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoGainConfig {
+    /// Seconds it takes to follow about two thirds of a rise in level.
+    pub attack: f32,
+    /// Seconds it takes to follow about two thirds of a fall in level.
+    pub release: f32,
+    /// Highest gain in dB applied to quiet passages, so that silence is not amplified into noise.
+    pub max_gain: f32
+}
+
+/// Tracks the peak level of the music and derives the gain which brings it to a target level.
+///
+/// The tracking depends on the time passed in the audio, not on how often it is updated.
+pub struct AutoGain {
+    config: AutoGainConfig,
+    /// The tracked peak level in dB.
+    level: Option<f32>
+}
+
+impl AutoGain {
+    pub fn new(config: AutoGainConfig) -> AutoGain {
+        AutoGain {
+            config: config,
+            level: None
+        }
+    }
+
+    /// Follow the current peak level and return the gain in dB to apply.
+    ///
+    /// # Arguments
+    ///
+    /// * `peak` - The current peak level in dB.
+    /// * `target` - Level in dB the tracked peak level should be brought to.
+    /// * `elapsed` - Seconds of audio since the last update.
+    pub fn update(&mut self, peak: f32, target: f32, elapsed: f32) -> f32 {
+        let level = match self.level {
+            Some(level) => {
+                let time = if peak > level { self.config.attack } else { self.config.release };
+                let factor = if time > 0.0 { 1.0 - (-elapsed / time).exp() } else { 1.0 };
+                level + (peak - level) * factor
+            },
+            None => peak
+        };
+        self.level = Some(level);
+        (target - level).min(self.config.max_gain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decibel() {
+        assert!(to_decibel(1.0).abs() < 0.001);
+        assert!((to_decibel(0.1) + 20.0).abs() < 0.001);
+        assert!((to_decibel(0.0) - SILENCE).abs() < 0.001);
+        assert!((from_decibel(-20.0) - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn scale() {
+        let range = DecibelRange::new(-60.0, 0.0).unwrap();
+        assert!((range.scale(-30.0) - 0.5).abs() < 0.001);
+        assert!(range.scale(-80.0).abs() < 0.001);
+        assert!((range.scale(10.0) - 1.0).abs() < 0.001);
+        assert!(DecibelRange::new(0.0, -60.0).is_err());
+    }
+
+    #[test]
+    fn auto_gain() {
+        let mut auto_gain = AutoGain::new(AutoGainConfig {
+            attack: 0.1,
+            release: 2.0,
+            max_gain: 30.0
+        });
+        assert!((auto_gain.update(-20.0, 0.0, 0.01) - 20.0).abs() < 0.001);
+        // A loud passage is followed quickly
+        let gain = (0 .. 30).map(|_| auto_gain.update(0.0, 0.0, 0.01)).last().unwrap();
+        assert!(gain.abs() < 1.0);
+        // A quiet passage only slowly
+        let gain = (0 .. 30).map(|_| auto_gain.update(-20.0, 0.0, 0.01)).last().unwrap();
+        assert!(gain > 0.0 && gain < 5.0);
+        // Silence is not amplified beyond the maximum gain
+        let gain = (0 .. 2000).map(|_| auto_gain.update(SILENCE, 0.0, 0.01)).last().unwrap();
+        assert!((gain - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn auto_gain_independent_of_rate() {
+        let config = AutoGainConfig {
+            attack: 0.1,
+            release: 2.0,
+            max_gain: 30.0
+        };
+        let mut slow = AutoGain::new(config);
+        let mut fast = AutoGain::new(config);
+        slow.update(-40.0, 0.0, 0.0);
+        fast.update(-40.0, 0.0, 0.0);
+        let slow_gain = (0 .. 10).map(|_| slow.update(0.0, 0.0, 0.02)).last().unwrap();
+        let fast_gain = (0 .. 40).map(|_| fast.update(0.0, 0.0, 0.005)).last().unwrap();
+        assert!((slow_gain - fast_gain).abs() < 0.001);
+    }
+}
@@ -0,0 +1,327 @@
+extern crate dft;
+
+pub mod bands;
+pub mod beat;
+pub mod level;
+pub mod source;
+pub mod window;
+
+use std::sync::mpsc::Sender;
+use dft::{Operation, Plan};
+use bus::{BusReader};
+use ControlStatus;
+use std::thread;
+use std::ops::Range;
+use core::cmp::Ordering;
+use self::bands::BandConfig;
+use self::beat::BeatDetector;
+use self::level::{to_decibel, from_decibel, AutoGain, AutoGainConfig, DecibelRange};
+use self::source::SourceConfig;
+use self::window::{RingBuffer, WindowFunction};
+
+const DFT_WINDOW_SIZE: usize = 2048;
+/// Amount of analyses per second if no hop size is configured, matching the frame rate of the targets.
+const ANALYSES_PER_SECOND: u32 = 60;
+
+/// Settings for analysing the audio.
+pub struct SpectrumConfig {
+    /// Amount of columns in the spectrum.
+    pub columns: usize,
+    /// Where the audio comes from.
+    pub source: SourceConfig,
+    /// Which frequencies are shown in each column.
+    pub bands: BandConfig,
+    /// Function the samples are weighted with before transforming them.
+    pub window: WindowFunction,
+    /// Amount of new samples between two analyses. Consecutive windows overlap if it is smaller than
+    /// the window. Defaults to one analysis per rendered frame.
+    pub hop: Option<usize>,
+    /// Levels shown as empty and as full height.
+    pub levels: DecibelRange,
+    /// How the gain follows the level of the music, or `None` to show the levels as they are.
+    pub auto_gain: Option<AutoGainConfig>,
+    /// Whether to analyse the left and right channel separately as well.
+    pub stereo: bool
+}
+
+/// Analysis of the left and right channel, each scaled the same way as the combined channels.
+#[derive(Clone)]
+pub struct StereoResult {
+    /// Spectrum of the left and of the right channel.
+    pub spectrum: [Vec<(f32, f32)>; 2],
+    /// Amplitude of the left and of the right channel.
+    pub amplitude: [Vec<[f32; 2]>; 2],
+    /// How similar both channels are, from -1 (inverted) over 0 (unrelated) to 1 (mono).
+    pub correlation: f32,
+    /// Which channel is louder, from -1 (only left) over 0 (centered) to 1 (only right).
+    pub balance: f32
+}
+
+#[derive(Clone)]
+pub struct SpectrumResult {
+    pub spectrum: Vec<(f32, f32)>,
+    pub amplitude: Vec<[f32; 2]>,
+    /// Analysis of the separate channels, if enabled.
+    pub stereo: Option<StereoResult>,
+    /// Whether an onset, usually a beat, was detected in this analysis.
+    pub beat: bool,
+    /// Estimated tempo in beats per minute, `None` until enough beats were detected.
+    pub bpm: Option<f32>
+}
+
+/// Magnitude of each frequency relative to full scale, so that a sine wave at full scale has a
+/// magnitude of 1 regardless of the window function.
+fn analyze(plan: &Plan<f32>, window: &[f32], samples: &[f32]) -> Vec<f32> {
+    let mut input = samples.iter().zip(window).map(|(sample, weight)| sample * weight).collect::<Vec<f32>>();
+    let scale = 2.0 / window.iter().sum::<f32>();
+    dft::transform(&mut input, plan);
+    dft::unpack(&input).iter().map(|frequency| {
+        frequency.norm() as f32 * scale
+    }).collect::<Vec<f32>>()
+}
+
+/// The lowest and highest level in dB within each column.
+fn get_spectrum(frequencies: &[f32], bins: &[Range<usize>]) -> Vec<(f32, f32)> {
+    bins.iter().map(|range| {
+        let column_min: f32 = range.clone()
+            .map(|index| frequencies[index])
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .unwrap_or(0.0f32);
+        let column_max: f32 = range.clone()
+            .map(|index| frequencies[index])
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .unwrap_or(0.0f32);
+        (to_decibel(column_min), to_decibel(column_max))
+    }).collect::<Vec<(f32, f32)>>()
+}
+
+/// Scale the levels of the spectrum onto the range from 0 to 1, applying the gain in dB.
+fn scale_spectrum(spectrum: &[(f32, f32)], levels: &DecibelRange, gain: f32) -> Vec<(f32, f32)> {
+    spectrum.iter()
+        .map(|&(min, max)| (levels.scale(min + gain), levels.scale(max + gain)))
+        .collect()
+}
+
+/// The gain in dB to apply, following the peak level if automatic gain is enabled.
+///
+/// # Arguments
+///
+/// * `auto_gain` - The automatic gain, `None` if disabled.
+/// * `peak` - The current peak level in dB.
+/// * `target` - Level in dB the peak level should be brought to.
+/// * `elapsed` - Seconds of audio since the last update.
+fn get_gain(auto_gain: Option<&mut AutoGain>, peak: f32, target: f32, elapsed: f32) -> f32 {
+    auto_gain.map(|auto_gain| auto_gain.update(peak, target, elapsed)).unwrap_or(0.0)
+}
+
+/// Append the lowest and highest sample to the amplitude, amplified by the gain in dB and limited to
+/// full scale.
+fn update_amplitude(amplitude: &mut Vec<[f32; 2]>, data: &[f32], gain: f32) {
+    let factor = from_decibel(gain);
+    let max = data.iter().cloned().fold(0.0, f32::max);
+    let min = data.iter().cloned().fold(0.0, f32::min);
+    amplitude.remove(0);
+    amplitude.push([(min * factor).max(-1.0), (max * factor).min(1.0)]);
+}
+
+/// Correlation between the left and the right channel.
+fn get_correlation(frames: &[[f32; 2]]) -> f32 {
+    let (product, left, right) = frames.iter().fold((0.0, 0.0, 0.0), |(product, left, right), frame| {
+        (product + frame[0] * frame[1], left + frame[0] * frame[0], right + frame[1] * frame[1])
+    });
+    if left > 0.0 && right > 0.0 {
+        (product / (left * right).sqrt()).max(-1.0).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Balance between the energy of the left and the right channel.
+fn get_balance(frames: &[[f32; 2]]) -> f32 {
+    let (left, right) = frames.iter().fold((0.0, 0.0), |(left, right), frame| {
+        (left + frame[0] * frame[0], right + frame[1] * frame[1])
+    });
+    if left + right > 0.0 {
+        (right - left) / (right + left)
+    } else {
+        0.0
+    }
+}
+
+/// A single channel of the frames.
+fn get_channel(frames: &[[f32; 2]], channel: usize) -> Vec<f32> {
+    frames.iter().map(|frame| frame[channel]).collect()
+}
+
+pub fn run(mut control_rx: BusReader<ControlStatus>,
+        sender: Sender<SpectrumResult>,
+        config: SpectrumConfig) -> Result<(), String> {
+    let columns = config.columns;
+    let levels = config.levels;
+    let mut source = try!(source::open(config.source));
+    let bins = config.bands.bins(columns, source.sample_rate(), DFT_WINDOW_SIZE);
+    let window = config.window.coefficients(DFT_WINDOW_SIZE);
+    let hop = config.hop.unwrap_or((source.sample_rate() / ANALYSES_PER_SECOND) as usize).max(1);
+    let mut ring_buffer = RingBuffer::new(DFT_WINDOW_SIZE);
+    let mut stereo_data = (0 .. hop).map(|_| [0.0, 0.0]).collect::<Vec<[f32;2]>>();
+    let plan = Plan::new(Operation::Forward, DFT_WINDOW_SIZE);
+    let mut amplitude = (0 .. columns).map(|_| [0.0, 0.0]).collect::<Vec<[f32;2]>>();
+    let elapsed = hop as f32 / source.sample_rate() as f32;
+    let mut spectrum_auto_gain = config.auto_gain.map(AutoGain::new);
+    let mut amplitude_auto_gain = config.auto_gain.map(AutoGain::new);
+    let mut channel_amplitude = [amplitude.clone(), amplitude.clone()];
+    let mut beat_detector = BeatDetector::new();
+    loop {
+        try!(source.read(&mut stereo_data[..]).map_err(|err| format!("Unable to read audio: {}", err)));
+        ring_buffer.push(&stereo_data);
+        let stereo_window = ring_buffer.frames();
+        let mono_window = stereo_window.iter().map(|samples| (samples[0] + samples[1]) / 2.0).collect::<Vec<f32>>();
+        let mono_data = stereo_data.iter().map(|samples| (samples[0] + samples[1]) / 2.0).collect::<Vec<f32>>();
+        let frequencies = analyze(&plan, &window, &mono_window);
+        let beat = beat_detector.update(&frequencies[.. DFT_WINDOW_SIZE / 2 + 1], elapsed);
+        let spectrum = get_spectrum(&frequencies, &bins);
+        let peak = spectrum.iter().map(|&(_, max)| max).fold(to_decibel(0.0), f32::max);
+        let spectrum_gain = get_gain(spectrum_auto_gain.as_mut(), peak, levels.ceiling(), elapsed);
+        let spectrum = scale_spectrum(&spectrum, &levels, spectrum_gain);
+        let peak = to_decibel(mono_data.iter().map(|sample| sample.abs()).fold(0.0, f32::max));
+        let amplitude_gain = get_gain(amplitude_auto_gain.as_mut(), peak, 0.0, elapsed);
+        update_amplitude(&mut amplitude, &mono_data, amplitude_gain);
+        let stereo = if config.stereo {
+            let channel_spectrum = |channel: usize| {
+                let spectrum = get_spectrum(&analyze(&plan, &window, &get_channel(&stereo_window, channel)), &bins);
+                scale_spectrum(&spectrum, &levels, spectrum_gain)
+            };
+            let spectrum = [channel_spectrum(0), channel_spectrum(1)];
+            for (channel, amplitude) in channel_amplitude.iter_mut().enumerate() {
+                update_amplitude(amplitude, &get_channel(&stereo_data, channel), amplitude_gain);
+            }
+            Some(StereoResult {
+                spectrum: spectrum,
+                amplitude: channel_amplitude.clone(),
+                correlation: get_correlation(&stereo_window),
+                balance: get_balance(&stereo_window)
+            })
+        } else {
+            None
+        };
+        try!(sender.send(SpectrumResult {
+            spectrum: spectrum,
+            amplitude: amplitude.clone(),
+            stereo: stereo,
+            beat: beat,
+            bpm: beat_detector.bpm()
+        }).map_err(|err| err.to_string()));
+        if let Ok(status) = control_rx.try_recv() {
+            if status == ControlStatus::Abort {
+                return Ok(())
+            }
+        }
+        thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use super::bands::{BandConfig, BandLayout};
+    use super::window::WindowFunction;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    /// A sine wave with the specified frequency, given in bins of the DFT.
+    fn sine(bin: f32) -> Vec<f32> {
+        (0 .. DFT_WINDOW_SIZE)
+            .map(|index| (2.0 * PI * bin * index as f32 / DFT_WINDOW_SIZE as f32).sin())
+            .collect()
+    }
+
+    fn peak(frequencies: &[f32]) -> usize {
+        (0 .. DFT_WINDOW_SIZE / 2)
+            .max_by(|a, b| frequencies[*a].partial_cmp(&frequencies[*b]).unwrap_or(Ordering::Equal))
+            .unwrap()
+    }
+
+    #[test]
+    fn analyze_peak() {
+        let plan = Plan::new(Operation::Forward, DFT_WINDOW_SIZE);
+        for function in &[WindowFunction::Rectangular, WindowFunction::Hann, WindowFunction::Hamming, WindowFunction::BlackmanHarris] {
+            let window = function.coefficients(DFT_WINDOW_SIZE);
+            assert_eq!(100, peak(&analyze(&plan, &window, &sine(100.0))));
+            assert_eq!(437, peak(&analyze(&plan, &window, &sine(437.0))));
+        }
+    }
+
+    #[test]
+    fn analyze_leakage() {
+        let plan = Plan::new(Operation::Forward, DFT_WINDOW_SIZE);
+        // Ratio of a bin far away from the peak to the peak, for a frequency between two bins
+        let leakage = |function: WindowFunction| {
+            let frequencies = analyze(&plan, &function.coefficients(DFT_WINDOW_SIZE), &sine(100.5));
+            frequencies[150] / frequencies[100]
+        };
+        let rectangular = leakage(WindowFunction::Rectangular);
+        assert!(rectangular > 0.005);
+        assert!(leakage(WindowFunction::Hann) < rectangular / 100.0);
+        assert!(leakage(WindowFunction::Hamming) < rectangular / 5.0);
+        assert!(leakage(WindowFunction::BlackmanHarris) < rectangular / 1000.0);
+    }
+
+    #[test]
+    fn get_spectrum_peak() {
+        let plan = Plan::new(Operation::Forward, DFT_WINDOW_SIZE);
+        let window = WindowFunction::Hann.coefficients(DFT_WINDOW_SIZE);
+        let bands = BandConfig::new(BandLayout::Logarithmic, 30.0, 16000.0).unwrap();
+        let bins = bands.bins(32, SAMPLE_RATE, DFT_WINDOW_SIZE);
+        // Bin 48 is 1125 Hz, which lies in column 18 covering 1013 Hz to 1242 Hz
+        assert_eq!(43 .. 53, bins[18]);
+        let samples = sine(48.0);
+        let spectrum = get_spectrum(&analyze(&plan, &window, &samples), &bins);
+        let column = (0 .. spectrum.len())
+            .max_by(|a, b| spectrum[*a].1.partial_cmp(&spectrum[*b].1).unwrap_or(Ordering::Equal))
+            .unwrap();
+        assert_eq!(18, column);
+        // A sine wave at full scale is at 0 dB
+        assert!(spectrum[column].1.abs() < 0.01);
+        assert!(spectrum[5].1 < -60.0);
+        let half = sine(48.0).iter().map(|sample| sample / 2.0).collect::<Vec<_>>();
+        assert!((get_spectrum(&analyze(&plan, &window, &half), &bins)[18].1 + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn get_correlation() {
+        let samples = sine(10.0);
+        let frames = |right: &Fn(f32) -> f32| samples.iter().map(|&sample| [sample, right(sample)]).collect::<Vec<_>>();
+        assert!((super::get_correlation(&frames(&|sample| sample / 2.0)) - 1.0).abs() < 0.001);
+        assert!((super::get_correlation(&frames(&|sample| -sample)) + 1.0).abs() < 0.001);
+        assert!(super::get_correlation(&frames(&|_| 0.0)).abs() < 0.001);
+        let unrelated = samples.iter().zip(sine(37.0)).map(|(&left, right)| [left, right]).collect::<Vec<_>>();
+        assert!(super::get_correlation(&unrelated).abs() < 0.01);
+    }
+
+    #[test]
+    fn get_balance() {
+        assert!((super::get_balance(&[[0.5, 0.0], [-0.5, 0.0]]) + 1.0).abs() < 0.001);
+        assert!((super::get_balance(&[[0.0, 0.5], [0.0, -0.5]]) - 1.0).abs() < 0.001);
+        assert!(super::get_balance(&[[0.5, -0.5], [0.25, 0.25]]).abs() < 0.001);
+        assert!(super::get_balance(&[[0.0, 0.0]]).abs() < 0.001);
+    }
+
+    #[test]
+    fn scale_spectrum() {
+        let levels = DecibelRange::new(-60.0, 0.0).unwrap();
+        let spectrum = super::scale_spectrum(&[(-90.0, -30.0), (-20.0, 10.0)], &levels, 10.0);
+        assert!(spectrum[0].0.abs() < 0.001);
+        assert!((spectrum[0].1 - 2.0 / 3.0).abs() < 0.001);
+        assert!((spectrum[1].1 - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn update_amplitude() {
+        let mut amplitude = vec![[0.0, 0.0]; 2];
+        super::update_amplitude(&mut amplitude, &[0.25, -0.125], 6.0206);
+        super::update_amplitude(&mut amplitude, &[0.75, -1.0], 6.0206);
+        assert!((amplitude[0][0] + 0.25).abs() < 0.001 && (amplitude[0][1] - 0.5).abs() < 0.001);
+        assert!((amplitude[1][0] + 1.0).abs() < 0.001 && (amplitude[1][1] - 1.0).abs() < 0.001);
+    }
+}
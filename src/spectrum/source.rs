@@ -0,0 +1,335 @@
+use std::io::{self, Cursor, Error, ErrorKind, SeekFrom};
+use std::io::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use pulse_simple::Record;
+
+/// Full scale of signed 16 bit samples.
+const S16_SCALE: f32 = 32768.0;
+
+/// Where the audio to analyse comes from.
+pub trait AudioSource {
+    /// Fill the buffer with the next frames, each holding the left and right sample from -1 to 1.
+    /// Blocks until enough audio is available.
+    fn read(&mut self, data: &mut [[f32; 2]]) -> io::Result<()>;
+
+    /// Amount of frames per second.
+    fn sample_rate(&self) -> u32;
+}
+
+/// Which audio source to analyse, along with its settings.
+pub enum SourceConfig {
+    /// Record from a PulseAudio source, usually the monitor of the output MPD plays to.
+    Pulse {
+        device: Option<String>,
+        sample_rate: u32
+    },
+    /// Read the raw signed 16 bit little endian stereo output of MPD's FIFO output.
+    Fifo {
+        path: String,
+        sample_rate: u32
+    },
+    /// Record from an ALSA device, usually the capture side of the loopback device MPD plays to.
+    Alsa {
+        device: String,
+        sample_rate: u32
+    },
+    /// Play a WAV file in a loop.
+    Wav(String)
+}
+
+/// Open the audio source described by the configuration.
+pub fn open(config: SourceConfig) -> Result<Box<AudioSource>, String> {
+    let source: Box<AudioSource> = match config {
+        SourceConfig::Pulse { device, sample_rate } => Box::new(PulseSource::new(device, sample_rate)),
+        SourceConfig::Fifo { path, sample_rate } => Box::new(try!(open_fifo(&path, sample_rate)
+            .map_err(|err| format!("Unable to open FIFO \"{}\": {}", path, err)))),
+        SourceConfig::Alsa { device, sample_rate } => Box::new(try!(AlsaSource::open(&device, sample_rate)
+            .map_err(|err| format!("Unable to record from ALSA device \"{}\": {}", device, err)))),
+        SourceConfig::Wav(path) => Box::new(try!(File::open(&path)
+            .and_then(|file| WavSource::new(file, true))
+            .map_err(|err| format!("Unable to read WAV file \"{}\": {}", path, err))))
+    };
+    Ok(source)
+}
+
+/// Records from PulseAudio.
+pub struct PulseSource {
+    record: Record<[f32; 2]>,
+    sample_rate: u32
+}
+
+impl PulseSource {
+    pub fn new(device: Option<String>, sample_rate: u32) -> PulseSource {
+        PulseSource {
+            record: Record::new("MusicPi Display", "Record", device.as_ref().map(String::as_str), sample_rate),
+            sample_rate: sample_rate
+        }
+    }
+}
+
+impl AudioSource for PulseSource {
+    fn read(&mut self, data: &mut [[f32; 2]]) -> io::Result<()> {
+        self.record.read(data);
+        Ok(())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// Reads raw signed 16 bit little endian stereo samples from any reader.
+pub struct RawSource<R: Read> {
+    reader: R,
+    sample_rate: u32,
+    buffer: Vec<u8>
+}
+
+impl<R: Read> RawSource<R> {
+    pub fn new(reader: R, sample_rate: u32) -> RawSource<R> {
+        RawSource {
+            reader: reader,
+            sample_rate: sample_rate,
+            buffer: Vec::new()
+        }
+    }
+}
+
+impl<R: Read> AudioSource for RawSource<R> {
+    fn read(&mut self, data: &mut [[f32; 2]]) -> io::Result<()> {
+        self.buffer.resize(data.len() * 4, 0);
+        try!(self.reader.read_exact(&mut self.buffer));
+        for (frame, bytes) in data.iter_mut().zip(self.buffer.chunks(4)) {
+            *frame = [
+                (bytes[0] as u16 | (bytes[1] as u16) << 8) as i16 as f32 / S16_SCALE,
+                (bytes[2] as u16 | (bytes[3] as u16) << 8) as i16 as f32 / S16_SCALE
+            ];
+        }
+        Ok(())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// Open the FIFO MPD writes to.
+///
+/// The FIFO is opened for writing as well, so that reading blocks instead of hitting the end of
+/// the file whenever MPD closes its end, for example when playback is stopped.
+pub fn open_fifo(path: &str, sample_rate: u32) -> io::Result<RawSource<File>> {
+    let file = try!(OpenOptions::new().read(true).write(true).open(path));
+    Ok(RawSource::new(file, sample_rate))
+}
+
+/// Records from ALSA by running `arecord`, which outputs the raw samples.
+pub struct AlsaSource {
+    child: Child,
+    source: RawSource<ChildStdout>
+}
+
+impl AlsaSource {
+    /// Start recording from the specified device, for example `hw:Loopback,1`.
+    pub fn open(device: &str, sample_rate: u32) -> io::Result<AlsaSource> {
+        let mut child = try!(Command::new("arecord")
+            .args(&["-q", "-D", device, "-t", "raw", "-f", "S16_LE", "-c", "2"])
+            .arg("-r").arg(sample_rate.to_string())
+            .stdout(Stdio::piped())
+            .spawn());
+        let stdout = child.stdout.take().unwrap();
+        Ok(AlsaSource {
+            child: child,
+            source: RawSource::new(stdout, sample_rate)
+        })
+    }
+}
+
+impl AudioSource for AlsaSource {
+    fn read(&mut self, data: &mut [[f32; 2]]) -> io::Result<()> {
+        self.source.read(data)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+}
+
+impl Drop for AlsaSource {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    bytes[0] as u16 | (bytes[1] as u16) << 8
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    read_u16(bytes) as u32 | (read_u16(&bytes[2 ..]) as u32) << 16
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Plays the signed 16 bit PCM samples of a mono or stereo WAV file in a loop.
+pub struct WavSource {
+    samples: Cursor<Vec<u8>>,
+    channels: u16,
+    sample_rate: u32,
+    /// Whether reading blocks until the frames would have been played.
+    realtime: bool,
+    /// When the first frame was read.
+    started: Option<Instant>,
+    frames_read: u64
+}
+
+impl WavSource {
+    /// Read the WAV file from the reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The WAV file.
+    /// * `realtime` - Whether reading blocks until the frames would have been played, instead of
+    ///   returning them right away.
+    pub fn new<R: Read>(mut reader: R, realtime: bool) -> io::Result<WavSource> {
+        let mut bytes = Vec::new();
+        try!(reader.read_to_end(&mut bytes));
+        if bytes.len() < 12 || &bytes[0 .. 4] != b"RIFF" || &bytes[8 .. 12] != b"WAVE" {
+            return Err(invalid("Not a WAV file."));
+        }
+        let mut format = None;
+        let mut position = 12;
+        while position + 8 <= bytes.len() {
+            let id = &bytes[position .. position + 4];
+            let size = read_u32(&bytes[position + 4 ..]) as usize;
+            let start = position + 8;
+            let end = (start + size).min(bytes.len());
+            if id == b"fmt " {
+                let chunk = &bytes[start .. end];
+                if chunk.len() < 16 {
+                    return Err(invalid("Invalid fmt chunk."));
+                }
+                format = Some((read_u16(chunk), read_u16(&chunk[2 ..]), read_u32(&chunk[4 ..]), read_u16(&chunk[14 ..])));
+            } else if id == b"data" {
+                let (tag, channels, sample_rate, bits) = try!(format.ok_or_else(|| invalid("Data before format.")));
+                if tag != 1 || bits != 16 || channels < 1 || channels > 2 {
+                    return Err(invalid("Only 16 bit PCM with one or two channels is supported."));
+                }
+                let frame_size = channels as usize * 2;
+                let samples = bytes[start .. start + (end - start) / frame_size * frame_size].to_vec();
+                if samples.is_empty() {
+                    return Err(invalid("No samples."));
+                }
+                return Ok(WavSource {
+                    samples: Cursor::new(samples),
+                    channels: channels,
+                    sample_rate: sample_rate,
+                    realtime: realtime,
+                    started: None,
+                    frames_read: 0
+                });
+            }
+            // Chunks are padded to an even size
+            position = start + size + size % 2;
+        }
+        Err(invalid("No data."))
+    }
+
+    fn read_sample(&mut self) -> f32 {
+        let mut bytes = [0u8; 2];
+        if self.samples.read_exact(&mut bytes).is_err() {
+            self.samples.seek(SeekFrom::Start(0)).unwrap();
+            self.samples.read_exact(&mut bytes).unwrap();
+        }
+        read_u16(&bytes) as i16 as f32 / S16_SCALE
+    }
+}
+
+impl AudioSource for WavSource {
+    fn read(&mut self, data: &mut [[f32; 2]]) -> io::Result<()> {
+        for frame in data.iter_mut() {
+            let left = self.read_sample();
+            let right = if self.channels == 2 { self.read_sample() } else { left };
+            *frame = [left, right];
+        }
+        if self.realtime {
+            if self.started.is_none() {
+                self.started = Some(Instant::now());
+            }
+            let started = self.started.unwrap();
+            let due = Duration::from_millis(self.frames_read * 1000 / self.sample_rate as u64);
+            let elapsed = started.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+        }
+        self.frames_read += data.len() as u64;
+        Ok(())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_source() {
+        let mut source = RawSource::new(Cursor::new(vec![0x00, 0x40, 0x00, 0xC0, 0xFF, 0x7F, 0x00, 0x80]), 44100);
+        let mut data = [[0.0; 2]; 2];
+        source.read(&mut data).unwrap();
+        assert_eq!([[0.5, -0.5], [32767.0 / 32768.0, -1.0]], data);
+        assert!(source.read(&mut data).is_err());
+    }
+
+    #[test]
+    fn wav_source_stereo() {
+        let wav = [
+            b"RIFF\0\0\0\0WAVEfmt ".to_vec(),
+            // PCM with 2 channels at 22050 Hz and 16 bits per sample
+            vec![16, 0, 0, 0, 1, 0, 2, 0, 0x22, 0x56, 0, 0, 0, 0, 0, 0, 4, 0, 16, 0],
+            // Chunks of odd size are followed by a padding byte
+            b"LIST\x03\0\0\0abc\0".to_vec(),
+            b"data\x08\0\0\0".to_vec(),
+            vec![0x00, 0x40, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x20]
+        ].concat();
+        let mut source = WavSource::new(Cursor::new(wav), false).unwrap();
+        assert_eq!(22050, source.sample_rate());
+        let mut data = [[0.0; 2]; 3];
+        source.read(&mut data).unwrap();
+        assert_eq!([[0.5, -0.5], [0.0, 0.25], [0.5, -0.5]], data);
+    }
+
+    #[test]
+    fn wav_source_mono() {
+        let wav = [
+            b"RIFF\0\0\0\0WAVEfmt ".to_vec(),
+            // PCM with 1 channel at 48000 Hz and 16 bits per sample
+            vec![16, 0, 0, 0, 1, 0, 1, 0, 0x80, 0xBB, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0],
+            b"data\x04\0\0\0".to_vec(),
+            vec![0x00, 0x40, 0x00, 0xE0]
+        ].concat();
+        let mut source = WavSource::new(Cursor::new(wav), false).unwrap();
+        let mut data = [[0.0; 2]; 2];
+        source.read(&mut data).unwrap();
+        assert_eq!([[0.5, 0.5], [-0.25, -0.25]], data);
+    }
+
+    #[test]
+    fn wav_source_invalid() {
+        assert!(WavSource::new(Cursor::new(b"RIFF\0\0\0\0WAVE".to_vec()), false).is_err());
+        assert!(WavSource::new(Cursor::new(b"OggS".to_vec()), false).is_err());
+        // Format chunk cut off by the end of the file
+        assert!(WavSource::new(Cursor::new(b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x02\0".to_vec()), false).is_err());
+        // Format chunk too short to describe the samples
+        assert!(WavSource::new(Cursor::new(b"RIFF\0\0\0\0WAVEfmt \x04\0\0\0\x01\0\x02\0".to_vec()), false).is_err());
+    }
+}
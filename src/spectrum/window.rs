@@ -0,0 +1,96 @@
+use std::f32::consts::PI;
+
+/// Function the samples are weighted with before transforming them, to reduce the leakage of
+/// frequencies into neighbouring bins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFunction {
+    /// No weighting at all, causing heavy leakage.
+    Rectangular,
+    Hann,
+    Hamming,
+    /// Four term Blackman-Harris, with the lowest leakage at the cost of wider peaks.
+    BlackmanHarris
+}
+
+impl WindowFunction {
+    pub fn parse(name: &str) -> Result<WindowFunction, String> {
+        match name {
+            "rectangular" => Ok(WindowFunction::Rectangular),
+            "hann" => Ok(WindowFunction::Hann),
+            "hamming" => Ok(WindowFunction::Hamming),
+            "blackman-harris" => Ok(WindowFunction::BlackmanHarris),
+            _ => Err(format!("Unknown window function \"{}\".", name))
+        }
+    }
+
+    /// The weights of a window of the specified size.
+    pub fn coefficients(&self, size: usize) -> Vec<f32> {
+        (0 .. size).map(|index| {
+            let phase = 2.0 * PI * index as f32 / size as f32;
+            match *self {
+                WindowFunction::Rectangular => 1.0,
+                WindowFunction::Hann => 0.5 - 0.5 * phase.cos(),
+                WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+                WindowFunction::BlackmanHarris =>
+                    0.35875 - 0.48829 * phase.cos() + 0.14128 * (2.0 * phase).cos() - 0.01168 * (3.0 * phase).cos()
+            }
+        }).collect()
+    }
+}
+
+/// Keeps the latest frames of samples, so that consecutive windows can overlap.
+pub struct RingBuffer {
+    frames: Vec<[f32; 2]>,
+    /// Index at which the next frame is written, which is also the oldest frame.
+    position: usize
+}
+
+impl RingBuffer {
+    /// Create a buffer holding the specified amount of frames, initially silent.
+    pub fn new(size: usize) -> RingBuffer {
+        RingBuffer {
+            frames: vec![[0.0, 0.0]; size],
+            position: 0
+        }
+    }
+
+    /// Append the frames, dropping the oldest ones.
+    pub fn push(&mut self, frames: &[[f32; 2]]) {
+        for frame in frames {
+            self.frames[self.position] = *frame;
+            self.position = (self.position + 1) % self.frames.len();
+        }
+    }
+
+    /// All frames in the buffer, from the oldest to the latest.
+    pub fn frames(&self) -> Vec<[f32; 2]> {
+        let mut frames = self.frames[self.position ..].to_vec();
+        frames.extend_from_slice(&self.frames[.. self.position]);
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coefficients() {
+        for function in &[WindowFunction::Hann, WindowFunction::Hamming, WindowFunction::BlackmanHarris] {
+            let coefficients = function.coefficients(8);
+            assert!((coefficients[4] - 1.0).abs() < 0.001);
+            assert!(coefficients[0] < 0.1);
+            assert!((coefficients[1] - coefficients[7]).abs() < 0.001);
+        }
+        assert_eq!(vec![1.0; 4], WindowFunction::Rectangular.coefficients(4));
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let mut buffer = RingBuffer::new(3);
+        buffer.push(&[[1.0, 1.0], [2.0, 2.0]]);
+        assert_eq!(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]], buffer.frames());
+        buffer.push(&[[3.0, 3.0], [4.0, 4.0]]);
+        assert_eq!(vec![[2.0, 2.0], [3.0, 3.0], [4.0, 4.0]], buffer.frames());
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::Timelike;
+use mpd::status::State;
+use info::Info;
+
+const MINUTES_PER_DAY: f32 = 1440.0;
+/// Highest value the intensity register of the MAX7219 accepts.
+pub const MAX_BRIGHTNESS: u8 = 15;
+
+/// When and how bright the display should be.
+#[derive(Clone)]
+pub struct BrightnessSchedule {
+    /// Brightness during the day.
+    pub day: u8,
+    /// Brightness during the night.
+    pub night: u8,
+    /// Minute of the day at which the day begins.
+    pub day_start: u32,
+    /// Minute of the day at which the night begins.
+    pub night_start: u32,
+    /// Minutes it takes to fade between day and night brightness.
+    pub fade: u32,
+    /// Brightness the display is dimmed to while playback is stopped.
+    pub stopped: Option<u8>,
+    /// Whether to scale the brightness with the volume of MPD.
+    pub follow_volume: bool
+}
+
+impl BrightnessSchedule {
+    /// Brightness following only the time of the day.
+    ///
+    /// # Arguments
+    ///
+    /// * `minute` - Minute of the day, including the fraction of the current minute.
+    fn level_at(&self, minute: f32) -> f32 {
+        let since = |start: u32| (minute - start as f32 + MINUTES_PER_DAY) % MINUTES_PER_DAY;
+        let progress = |since: f32| if self.fade == 0 { 1.0 } else { (since / self.fade as f32).min(1.0) };
+        let (day, night) = (self.day as f32, self.night as f32);
+        let since_day = since(self.day_start);
+        let since_night = since(self.night_start);
+        if since_day < since_night {
+            night + (day - night) * progress(since_day)
+        } else {
+            day + (night - day) * progress(since_night)
+        }
+    }
+
+    /// The brightness the display should have according to the specified info.
+    pub fn brightness(&self, info: &Info) -> u8 {
+        let time = info.time;
+        let minute = (time.hour() * 60 + time.minute()) as f32 + time.second() as f32 / 60.0;
+        let mut level = self.level_at(minute);
+        if self.follow_volume && info.volume >= 0 {
+            level = level * info.volume as f32 / 100.0;
+        }
+        if let Some(stopped) = self.stopped {
+            if !info.connected || info.state == State::Stop {
+                level = level.min(stopped as f32);
+            }
+        }
+        (level.round() as u8).min(MAX_BRIGHTNESS)
+    }
+}
+
+/// Parse a time of the day in the format `HH:MM` into the minute of the day.
+pub fn parse_time(time: &str) -> Result<u32, String> {
+    let parts = time.split(':').collect::<Vec<_>>();
+    if parts.len() == 2 {
+        if let (Ok(hours), Ok(minutes)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+            if hours < 24 && minutes < 60 {
+                return Ok(hours * 60 + minutes);
+            }
+        }
+    }
+    Err(format!("Invalid time \"{}\", expected HH:MM.", time))
+}
+
+/// Keeps track of the brightness of the display, reporting when it needs to be changed.
+pub struct BrightnessController {
+    schedule: BrightnessSchedule,
+    current: Option<u8>
+}
+
+impl BrightnessController {
+    pub fn new(schedule: BrightnessSchedule) -> BrightnessController {
+        BrightnessController {
+            schedule: schedule,
+            current: None
+        }
+    }
+
+    /// Returns the new brightness if it differs from the one reported last time.
+    pub fn update(&mut self, info: &Info) -> Option<u8> {
+        let brightness = self.schedule.brightness(info);
+        if self.current == Some(brightness) {
+            None
+        } else {
+            self.current = Some(brightness);
+            Some(brightness)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std;
+    use mpd::status::State;
+    use test_helpers::*;
+
+    /// Schedule with the same level at any time of the day, as the info uses the current time.
+    fn create_constant_schedule(level: u8, stopped: Option<u8>, follow_volume: bool) -> BrightnessSchedule {
+        BrightnessSchedule {
+            day: level,
+            night: level,
+            day_start: 7 * 60,
+            night_start: 22 * 60,
+            fade: 30,
+            stopped: stopped,
+            follow_volume: follow_volume
+        }
+    }
+
+    #[test]
+    fn level_at() {
+        let schedule = BrightnessSchedule {
+            day: 9,
+            night: 1,
+            day_start: 7 * 60,
+            night_start: 22 * 60,
+            fade: 30,
+            stopped: None,
+            follow_volume: false
+        };
+        assert!((schedule.level_at(3.0 * 60.0) - 1.0).abs() < std::f32::EPSILON);
+        assert!((schedule.level_at(7.0 * 60.0 + 15.0) - 5.0).abs() < std::f32::EPSILON);
+        assert!((schedule.level_at(12.0 * 60.0) - 9.0).abs() < std::f32::EPSILON);
+        assert!((schedule.level_at(22.0 * 60.0 + 15.0) - 5.0).abs() < std::f32::EPSILON);
+        assert!((schedule.level_at(23.5 * 60.0) - 1.0).abs() < std::f32::EPSILON);
+    }
+
+    #[test]
+    fn level_at_without_fade() {
+        let schedule = BrightnessSchedule {
+            day: 9,
+            night: 1,
+            day_start: 7 * 60,
+            night_start: 22 * 60,
+            fade: 0,
+            stopped: None,
+            follow_volume: false
+        };
+        assert!((schedule.level_at(7.0 * 60.0) - 9.0).abs() < std::f32::EPSILON);
+        assert!((schedule.level_at(22.0 * 60.0) - 1.0).abs() < std::f32::EPSILON);
+    }
+
+    #[test]
+    fn brightness_volume() {
+        let schedule = create_constant_schedule(10, None, true);
+        assert_eq!(0, schedule.brightness(&create_test_info(true, State::Play, 0)));
+        assert_eq!(5, schedule.brightness(&create_test_info(true, State::Play, 50)));
+        assert_eq!(10, schedule.brightness(&create_test_info(true, State::Play, 100)));
+        // MPD reports -1 without a mixer
+        assert_eq!(10, schedule.brightness(&create_test_info(true, State::Play, -1)));
+        let schedule = create_constant_schedule(10, None, false);
+        assert_eq!(10, schedule.brightness(&create_test_info(true, State::Play, 0)));
+    }
+
+    #[test]
+    fn brightness_stopped() {
+        let schedule = create_constant_schedule(10, Some(2), false);
+        assert_eq!(10, schedule.brightness(&create_test_info(true, State::Play, 100)));
+        assert_eq!(10, schedule.brightness(&create_test_info(true, State::Pause, 100)));
+        assert_eq!(2, schedule.brightness(&create_test_info(true, State::Stop, 100)));
+        assert_eq!(2, schedule.brightness(&create_test_info(false, State::Play, 100)));
+        let schedule = create_constant_schedule(1, Some(2), false);
+        assert_eq!(1, schedule.brightness(&create_test_info(true, State::Stop, 100)));
+    }
+
+    #[test]
+    fn brightness_clamped() {
+        let schedule = create_constant_schedule(MAX_BRIGHTNESS + 5, None, false);
+        assert_eq!(MAX_BRIGHTNESS, schedule.brightness(&create_test_info(true, State::Play, 100)));
+    }
+
+    #[test]
+    fn controller() {
+        let mut controller = BrightnessController::new(create_constant_schedule(10, Some(2), false));
+        let playing = create_test_info(true, State::Play, 100);
+        let stopped = create_test_info(true, State::Stop, 100);
+        assert_eq!(Some(10), controller.update(&playing));
+        assert_eq!(None, controller.update(&playing));
+        assert_eq!(Some(2), controller.update(&stopped));
+        assert_eq!(None, controller.update(&stopped));
+        assert_eq!(Some(10), controller.update(&playing));
+    }
+
+    #[test]
+    fn parse_time() {
+        assert_eq!(Ok(7 * 60 + 30), super::parse_time("07:30"));
+        assert!(super::parse_time("24:00").is_err());
+        assert!(super::parse_time("7").is_err());
+    }
+}
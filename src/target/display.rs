@@ -0,0 +1,134 @@
+use sdl2::surface::Surface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Renderer;
+use display::Display;
+use display::mapping::DeviceMapping;
+use display::transport::{open_spidev, FileTransport, Transport};
+use info::Info;
+use spectrum::SpectrumResult;
+use std::sync::mpsc::Receiver;
+use std::slice::from_raw_parts;
+use target::{BaseTarget, Target};
+use target::brightness::{BrightnessController, BrightnessSchedule};
+use target::dither::Dither;
+use dimensions::Dimensions;
+
+/// Read the intensity of each pixel rendered onto the surface of the renderer, from 0 to 255.
+pub fn read_intensities(renderer: &Renderer, dimensions: Dimensions) -> Vec<u8> {
+    let pixels = unsafe { from_raw_parts((*renderer.surface().unwrap().raw()).pixels as *const u32, dimensions.pixels()) };
+    pixels.into_iter()
+        .map(|pixel| (*pixel & 0xFF) as u8)
+        .collect()
+}
+
+/// Read which pixels rendered onto the surface of the renderer are switched on.
+///
+/// Without dithering only fully opaque pixels are on.
+pub fn read_pixels(renderer: &Renderer, dither: Option<&mut Dither>, dimensions: Dimensions) -> Vec<bool> {
+    let intensities = read_intensities(renderer, dimensions);
+    match dither {
+        Some(dither) => dither.apply(&intensities),
+        None => intensities.into_iter().map(|intensity| intensity == 0xFF).collect::<Vec<_>>()
+    }
+}
+
+fn update_display(renderer: &Renderer,
+        display: &mut Display<Box<Transport>>,
+        dither: Option<&mut Dither>,
+        dimensions: Dimensions) {
+    let display_data = read_pixels(renderer, dither, dimensions);
+    display.display(&display_data).unwrap();
+}
+
+/// Settings for driving the chain of MAX7219 controllers.
+pub struct DisplayConfig {
+    /// Part of the frame shown by each device in the chain and its orientation.
+    pub mapping: Vec<DeviceMapping>,
+    /// File or FIFO to write the data for the controllers to instead of the SPI device.
+    pub output: Option<String>,
+    /// Path of the SPI device the controllers are connected to.
+    pub spi_device: String,
+    /// Clock speed of the SPI bus in Hz.
+    pub spi_speed: u32,
+    /// When and how bright the display should be.
+    pub brightness: BrightnessSchedule,
+    /// Whether to show intermediate intensities by switching pixels on and off over multiple frames.
+    pub dither: bool
+}
+
+/// Open the transport configured and setup the chain of controllers on it, clearing the display.
+pub fn open_display(dimensions: Dimensions, config: &DisplayConfig) -> Result<Display<Box<Transport>>, String> {
+    if dimensions.width % 8 != 0 || dimensions.height % 8 != 0 {
+        return Err(format!("Dimensions {}x{} are not a multiple of the 8x8 devices.", dimensions.width, dimensions.height));
+    }
+    let transport: Box<Transport> = match config.output {
+        Some(ref path) => Box::new(try!(FileTransport::open(path)
+            .map_err(|err| format!("Unable to open \"{}\": {}", path, err)))),
+        None => Box::new(try!(open_spidev(&config.spi_device, config.spi_speed)
+            .map_err(|err| format!("Unable to open SPI device \"{}\": {}", config.spi_device, err))))
+    };
+    let mut display = try!(Display::new(transport,
+            dimensions.width as usize / 8,
+            dimensions.height as usize / 8,
+            config.mapping.clone())
+        .map_err(|err| format!("Unable to setup the display: {}", err)));
+    try!(display.clear().map_err(|err| format!("Unable to clear the display: {}", err)));
+    Ok(display)
+}
+
+pub struct TargetDisplay {
+    display: Display<Box<Transport>>,
+    brightness: BrightnessController,
+    dither: Option<Dither>,
+    base_target: BaseTarget
+}
+
+impl TargetDisplay {
+    pub fn new(info_receiver: Receiver<Info>,
+            spectrum_receiver: Receiver<SpectrumResult>,
+            dimensions: Dimensions,
+            config: DisplayConfig) -> Result<TargetDisplay, String> {
+        let mut display = try!(open_display(dimensions, &config));
+        let surface = Surface::new(dimensions.width, dimensions.height, PixelFormatEnum::RGBA8888).unwrap();
+        let renderer = Renderer::from_surface(surface).unwrap();
+        let info = info_receiver.recv().unwrap();
+        let spectrum = spectrum_receiver.recv().unwrap();
+        let mut brightness = BrightnessController::new(config.brightness);
+        if let Some(intensity) = brightness.update(&info) {
+            display.set_intensity(intensity).unwrap();
+        }
+        Ok(TargetDisplay {
+            display: display,
+            brightness: brightness,
+            dither: if config.dither { Some(Dither::new(dimensions.pixels())) } else { None },
+            base_target: BaseTarget {
+                dimensions: dimensions,
+                renderer: renderer,
+                info: info,
+                spectrum: spectrum,
+                info_receiver: info_receiver,
+                spectrum_receiver: spectrum_receiver
+            }
+        })
+    }
+
+}
+
+impl Target for TargetDisplay {
+    fn base_target(&mut self) -> &mut BaseTarget {
+        &mut self.base_target
+    }
+
+    fn render(&mut self) -> bool {
+        if let Some(intensity) = self.brightness.update(&self.base_target.info) {
+            self.display.set_intensity(intensity).unwrap();
+        }
+        update_display(&self.base_target.renderer,
+            &mut self.display,
+            self.dither.as_mut(),
+            self.base_target.dimensions);
+        true
+    }
+}
+
+
@@ -0,0 +1,44 @@
+/// Turns intermediate intensities into patterns of pixels switching on and off over multiple frames,
+/// so that a pixel is on for a share of the frames matching its intensity.
+pub struct Dither {
+    /// Intensity each pixel has accumulated without having been switched on.
+    accumulators: Vec<u32>
+}
+
+impl Dither {
+    pub fn new(pixels: usize) -> Dither {
+        Dither {
+            accumulators: vec![0; pixels]
+        }
+    }
+
+    /// Decide which pixels to switch on for the next frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensities` - The intensity of each pixel from 0 (off) to 255 (on).
+    pub fn apply(&mut self, intensities: &[u8]) -> Vec<bool> {
+        intensities.iter().zip(self.accumulators.iter_mut()).map(|(&intensity, accumulator)| {
+            *accumulator += intensity as u32;
+            if *accumulator >= 255 {
+                *accumulator -= 255;
+                true
+            } else {
+                false
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let mut dither = Dither::new(4);
+        let frames = (0 .. 4).map(|_| dither.apply(&[0, 64, 128, 255])).collect::<Vec<_>>();
+        let on = (0 .. 4).map(|pixel| frames.iter().filter(|frame| frame[pixel]).count()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2, 4], on);
+    }
+}
@@ -0,0 +1,159 @@
+use sdl2::surface::Surface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Renderer;
+use sdl2_image::SaveSurface;
+use info::Info;
+use spectrum::SpectrumResult;
+use std::sync::mpsc::Receiver;
+use std::slice::from_raw_parts_mut;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use target::{BaseTarget, Target, MILLISECONDS_PER_FRAME};
+use target::display::read_intensities;
+use dimensions::Dimensions;
+use gif::GifEncoder;
+
+/// Shortest delay in hundredths of a second browsers show a frame of a GIF for, shorter delays are
+/// slowed down to 10 frames per second.
+const MIN_GIF_DELAY: u64 = 2;
+
+/// Where to write the frames to.
+pub enum FramesOutput {
+    /// One PNG file per frame in the specified directory.
+    Png(String),
+    /// One animated GIF containing all frames.
+    Gif(String)
+}
+
+/// Settings for dumping the rendered frames into files.
+pub struct FramesConfig {
+    pub output: FramesOutput,
+    /// Only every Nth frame is written, `None` to write as many frames as the output can show.
+    pub interval: Option<u32>,
+    /// Amount of frames after which to stop rendering, `None` to write frames until aborted.
+    pub count: Option<u32>
+}
+
+enum Writer {
+    Png(PathBuf),
+    Gif(GifEncoder<File>)
+}
+
+/// Create a surface with the pixels in shades of grey from white (off) to black (on).
+fn create_surface(intensities: &[u8], dimensions: Dimensions) -> Result<Surface<'static>, String> {
+    let mut surface = try!(Surface::new(dimensions.width, dimensions.height, PixelFormatEnum::RGBA8888));
+    surface.with_lock_mut(|bytes| {
+        let pixels = unsafe { from_raw_parts_mut(bytes.as_mut_ptr() as *mut u32, intensities.len()) };
+        for (pixel, &intensity) in pixels.iter_mut().zip(intensities) {
+            let shade = 255 - intensity as u32;
+            *pixel = shade << 24 | shade << 16 | shade << 8 | 0xFF;
+        }
+    });
+    Ok(surface)
+}
+
+pub struct TargetFrames {
+    base_target: BaseTarget,
+    writer: Writer,
+    interval: u32,
+    frame: u64,
+    /// Amount of frames written so far.
+    written: u32,
+    count: Option<u32>,
+    /// Milliseconds the delays of the GIF fell short of the time between its frames due to rounding.
+    delay_remainder: u64
+}
+
+impl TargetFrames {
+    pub fn new(info_receiver: Receiver<Info>,
+            spectrum_receiver: Receiver<SpectrumResult>,
+            dimensions: Dimensions,
+            config: FramesConfig) -> Result<TargetFrames, String> {
+        let surface = Surface::new(dimensions.width, dimensions.height, PixelFormatEnum::RGBA8888).unwrap();
+        let renderer = Renderer::from_surface(surface).unwrap();
+        let min_interval = match config.output {
+            FramesOutput::Png(_) => 1,
+            FramesOutput::Gif(_) => ((MIN_GIF_DELAY * 10 + MILLISECONDS_PER_FRAME - 1) / MILLISECONDS_PER_FRAME) as u32
+        };
+        let interval = config.interval.unwrap_or(min_interval);
+        if interval < min_interval {
+            return Err(format!("The frame interval needs to be at least {}, as GIFs can not show frames for less than {} ms.",
+                min_interval, MIN_GIF_DELAY * 10));
+        }
+        let writer = match config.output {
+            FramesOutput::Png(directory) => {
+                try!(fs::create_dir_all(&directory)
+                    .map_err(|err| format!("Unable to create the directory \"{}\": {}", directory, err)));
+                Writer::Png(PathBuf::from(directory))
+            },
+            FramesOutput::Gif(path) => {
+                let file = try!(File::create(&path).map_err(|err| format!("Unable to create \"{}\": {}", path, err)));
+                Writer::Gif(try!(GifEncoder::new(file, dimensions.width as u16, dimensions.height as u16)
+                    .map_err(|err| format!("Unable to write \"{}\": {}", path, err))))
+            }
+        };
+        let info = info_receiver.recv().unwrap();
+        let spectrum = spectrum_receiver.recv().unwrap();
+        Ok(TargetFrames {
+            writer: writer,
+            interval: interval,
+            frame: 0,
+            written: 0,
+            count: config.count,
+            delay_remainder: 0,
+            base_target: BaseTarget {
+                dimensions: dimensions,
+                renderer: renderer,
+                info: info,
+                spectrum: spectrum,
+                info_receiver: info_receiver,
+                spectrum_receiver: spectrum_receiver
+            }
+        })
+    }
+
+    fn write_frame(&mut self) -> Result<(), String> {
+        let dimensions = self.base_target.dimensions;
+        let intensities = read_intensities(&self.base_target.renderer, dimensions);
+        match self.writer {
+            Writer::Png(ref directory) => {
+                let path = directory.join(format!("frame-{:06}.png", self.frame));
+                try!(create_surface(&intensities, dimensions)).save(&path)
+            },
+            Writer::Gif(ref mut encoder) => {
+                // Carry the rounding remainder over, so that the GIF plays at the right speed
+                let milliseconds = self.interval as u64 * MILLISECONDS_PER_FRAME + self.delay_remainder;
+                self.delay_remainder = milliseconds % 10;
+                encoder.frame(&intensities, (milliseconds / 10) as u16).map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+impl Target for TargetFrames {
+    fn base_target(&mut self) -> &mut BaseTarget {
+        &mut self.base_target
+    }
+
+    fn render(&mut self) -> bool {
+        if self.frame % self.interval as u64 == 0 {
+            if let Err(err) = self.write_frame() {
+                println!("Unable to write frame: {}", err);
+                return false;
+            }
+            self.written += 1;
+        }
+        self.frame += 1;
+        match self.count {
+            Some(count) => self.written < count,
+            None => true
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        match self.writer {
+            Writer::Png(_) => Ok(()),
+            Writer::Gif(ref mut encoder) => encoder.finish().map_err(|err| format!("Unable to finish the GIF: {}", err))
+        }
+    }
+}
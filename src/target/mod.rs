@@ -0,0 +1,130 @@
+pub mod brightness;
+pub mod display;
+pub mod dither;
+pub mod frames;
+pub mod network;
+pub mod terminal;
+pub mod window;
+
+use graphics::{Graphics, GraphicsConfig};
+use graphics::clock::Clock;
+use info::Info;
+use spectrum::SpectrumResult;
+use sdl2::render::Renderer;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use sdl2_image::{self, INIT_PNG};
+use bus::BusReader;
+use ControlStatus;
+use std::time::{SystemTime, Duration};
+use dimensions::Dimensions;
+use self::display::{DisplayConfig, TargetDisplay};
+use self::frames::{FramesConfig, TargetFrames};
+use self::network::{NetworkConfig, TargetNetwork};
+use self::terminal::{TargetTerminal, TerminalConfig};
+use self::window::TargetWindow;
+
+const MILLISECONDS_PER_FRAME: u64 = 1000/60;
+
+/// Which target to render to, along with its settings.
+pub enum TargetConfig {
+    Display(DisplayConfig),
+    Window,
+    Frames(FramesConfig),
+    Terminal(TerminalConfig),
+    Network(NetworkConfig)
+}
+
+/// Create the target described by the configuration.
+pub fn create(config: TargetConfig,
+        info_receiver: Receiver<Info>,
+        spectrum_receiver: Receiver<SpectrumResult>,
+        dimensions: Dimensions) -> Result<Box<Target>, String> {
+    let target: Box<Target> = match config {
+        TargetConfig::Display(config) =>
+            Box::new(try!(TargetDisplay::new(info_receiver, spectrum_receiver, dimensions, config))),
+        TargetConfig::Window =>
+            Box::new(try!(TargetWindow::new(info_receiver, spectrum_receiver, dimensions))),
+        TargetConfig::Frames(config) =>
+            Box::new(try!(TargetFrames::new(info_receiver, spectrum_receiver, dimensions, config))),
+        TargetConfig::Terminal(config) =>
+            Box::new(try!(TargetTerminal::new(info_receiver, spectrum_receiver, dimensions, config))),
+        TargetConfig::Network(config) =>
+            Box::new(try!(TargetNetwork::new(info_receiver, spectrum_receiver, dimensions, config)))
+    };
+    Ok(target)
+}
+
+pub struct BaseTarget {
+    dimensions: Dimensions,
+    renderer: Renderer<'static>,
+    info: Info,
+    spectrum: SpectrumResult,
+    info_receiver: Receiver<Info>,
+    spectrum_receiver: Receiver<SpectrumResult>
+}
+
+impl BaseTarget {
+    pub fn renderer(&mut self) -> &mut Renderer<'static> {
+        &mut self.renderer
+    }
+}
+
+pub trait Target {
+    fn run(& mut self, mut control_rx: BusReader<ControlStatus>, config: GraphicsConfig) -> Result<(), String> {
+        try!(sdl2_image::init(INIT_PNG));
+        let mut graphics = {
+            let base_target = self.base_target();
+            let dimensions = base_target.dimensions;
+            let renderer = base_target.renderer();
+            Graphics::new(renderer, dimensions, config)
+        };
+        let clock = Clock::new();
+        'a: loop {
+            let begin = SystemTime::now();
+            {
+                let mut base_target = self.base_target();
+                let result = base_target.info_receiver.try_recv();
+                if result.is_ok() {
+                    base_target.info = result.unwrap();
+                }
+                // The analysis may run faster than the frames are rendered, only the newest result is shown
+                while let Ok(spectrum_result) = base_target.spectrum_receiver.try_recv() {
+                    base_target.spectrum = spectrum_result;
+                }
+                try!(graphics.draw(&mut base_target.renderer,
+                    base_target.info.clone(),
+                    base_target.spectrum.clone(),
+                    clock.now()));
+            }
+            if !self.render() {
+                break 'a;
+            }
+            if let Ok(status) = control_rx.try_recv() {
+                if status == ControlStatus::Abort {
+                    break 'a;
+                }
+            }
+            let elapsed = SystemTime::now().duration_since(begin).expect("System time error occured.");
+            let desired_duration = Duration::from_millis(MILLISECONDS_PER_FRAME);
+            if elapsed < desired_duration {
+                let sleep_time = desired_duration - elapsed;
+                thread::sleep(sleep_time);
+            } else {
+                println!("Warning, rendering took too long: {:.3}ms", elapsed.subsec_nanos() / 1_000_000);
+            }
+        }
+        self.finish()
+    }
+
+    fn base_target(&mut self) -> &mut BaseTarget;
+
+    /// Show the frame just drawn, returning whether to continue rendering.
+    fn render(&mut self) -> bool;
+
+    /// Complete the output once rendering stopped.
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
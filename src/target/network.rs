@@ -0,0 +1,78 @@
+use sdl2::surface::Surface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Renderer;
+use info::Info;
+use spectrum::SpectrumResult;
+use std::sync::mpsc::Receiver;
+use network::{Connection, Frame, Protocol};
+use target::{BaseTarget, Target};
+use target::brightness::BrightnessSchedule;
+use target::display::read_pixels;
+use target::dither::Dither;
+use dimensions::Dimensions;
+
+/// Settings for streaming the frames to displays on other machines.
+pub struct NetworkConfig {
+    pub protocol: Protocol,
+    /// Address of the receiver, for example `musicpi-zero:7219`.
+    pub address: String,
+    /// When and how bright the receiving displays should be.
+    pub brightness: BrightnessSchedule,
+    /// Whether to show intermediate intensities by switching pixels on and off over multiple frames.
+    pub dither: bool
+}
+
+pub struct TargetNetwork {
+    base_target: BaseTarget,
+    connection: Connection,
+    brightness: BrightnessSchedule,
+    dither: Option<Dither>,
+    sequence: u32
+}
+
+impl TargetNetwork {
+    pub fn new(info_receiver: Receiver<Info>,
+            spectrum_receiver: Receiver<SpectrumResult>,
+            dimensions: Dimensions,
+            config: NetworkConfig) -> Result<TargetNetwork, String> {
+        let connection = try!(Connection::open(config.protocol, &config.address));
+        let surface = Surface::new(dimensions.width, dimensions.height, PixelFormatEnum::RGBA8888).unwrap();
+        let renderer = Renderer::from_surface(surface).unwrap();
+        let info = info_receiver.recv().unwrap();
+        let spectrum = spectrum_receiver.recv().unwrap();
+        Ok(TargetNetwork {
+            connection: connection,
+            brightness: config.brightness,
+            dither: if config.dither { Some(Dither::new(dimensions.pixels())) } else { None },
+            sequence: 0,
+            base_target: BaseTarget {
+                dimensions: dimensions,
+                renderer: renderer,
+                info: info,
+                spectrum: spectrum,
+                info_receiver: info_receiver,
+                spectrum_receiver: spectrum_receiver
+            }
+        })
+    }
+}
+
+impl Target for TargetNetwork {
+    fn base_target(&mut self) -> &mut BaseTarget {
+        &mut self.base_target
+    }
+
+    fn render(&mut self) -> bool {
+        let dimensions = self.base_target.dimensions;
+        let frame = Frame {
+            sequence: self.sequence,
+            brightness: self.brightness.brightness(&self.base_target.info),
+            width: dimensions.width as u16,
+            height: dimensions.height as u16,
+            pixels: read_pixels(&self.base_target.renderer, self.dither.as_mut(), dimensions)
+        };
+        self.connection.send(&frame.encode());
+        self.sequence = self.sequence.wrapping_add(1);
+        true
+    }
+}
@@ -0,0 +1,173 @@
+use sdl2::surface::Surface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Renderer;
+use info::Info;
+use spectrum::SpectrumResult;
+use std::sync::mpsc::Receiver;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime};
+use target::{BaseTarget, Target};
+use target::display::read_pixels;
+use dimensions::Dimensions;
+
+/// Move the cursor to the top left corner.
+const CURSOR_HOME: &'static str = "\x1b[H";
+const CLEAR_SCREEN: &'static str = "\x1b[2J";
+const HIDE_CURSOR: &'static str = "\x1b[?25l";
+const SHOW_CURSOR: &'static str = "\x1b[?25h";
+
+/// Characters used to print the pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerminalStyle {
+    /// One character per 1x2 pixels using the upper and lower half block.
+    HalfBlock,
+    /// One character per 2x4 pixels using braille patterns.
+    Braille
+}
+
+/// Settings for printing the frames into the terminal.
+pub struct TerminalConfig {
+    pub style: TerminalStyle,
+    /// Maximum amount of times per second the frame is printed.
+    pub fps: u32
+}
+
+/// Print the pixels using one half block character per two rows.
+fn draw_half_block(pixels: &[bool], dimensions: Dimensions) -> String {
+    let (width, height) = (dimensions.width as usize, dimensions.height as usize);
+    let pixel = |x: usize, y: usize| y < height && pixels[y * width + x];
+    let mut result = String::new();
+    for y in (0 .. height).filter(|y| y % 2 == 0) {
+        for x in 0 .. width {
+            result.push(match (pixel(x, y), pixel(x, y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' '
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Print the pixels using one braille pattern per block of 2x4 pixels.
+fn draw_braille(pixels: &[bool], dimensions: Dimensions) -> String {
+    // Bit of the pattern for each dot, indexed by row and then column
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+    let (width, height) = (dimensions.width as usize, dimensions.height as usize);
+    let mut result = String::new();
+    for top in (0 .. height).filter(|y| y % 4 == 0) {
+        for left in (0 .. width).filter(|x| x % 2 == 0) {
+            let mut pattern = 0;
+            for (row, dots) in DOTS.iter().enumerate() {
+                for (column, dot) in dots.iter().enumerate() {
+                    let (x, y) = (left + column, top + row);
+                    if x < width && y < height && pixels[y * width + x] {
+                        pattern |= *dot;
+                    }
+                }
+            }
+            result.push(::std::char::from_u32(0x2800 + pattern).unwrap());
+        }
+        result.push('\n');
+    }
+    result
+}
+
+pub struct TargetTerminal {
+    base_target: BaseTarget,
+    style: TerminalStyle,
+    interval: Duration,
+    last_draw: Option<SystemTime>,
+    /// The frame as it was printed last, to skip printing unchanged frames.
+    previous: String
+}
+
+impl TargetTerminal {
+    pub fn new(info_receiver: Receiver<Info>,
+            spectrum_receiver: Receiver<SpectrumResult>,
+            dimensions: Dimensions,
+            config: TerminalConfig) -> Result<TargetTerminal, String> {
+        let surface = Surface::new(dimensions.width, dimensions.height, PixelFormatEnum::RGBA8888).unwrap();
+        let renderer = Renderer::from_surface(surface).unwrap();
+        let info = info_receiver.recv().unwrap();
+        let spectrum = spectrum_receiver.recv().unwrap();
+        print!("{}{}", CLEAR_SCREEN, HIDE_CURSOR);
+        Ok(TargetTerminal {
+            style: config.style,
+            interval: Duration::from_millis(1000 / config.fps.max(1) as u64),
+            last_draw: None,
+            previous: String::new(),
+            base_target: BaseTarget {
+                dimensions: dimensions,
+                renderer: renderer,
+                info: info,
+                spectrum: spectrum,
+                info_receiver: info_receiver,
+                spectrum_receiver: spectrum_receiver
+            }
+        })
+    }
+
+    /// Whether enough time has passed since the frame was printed last.
+    fn is_due(&self, now: SystemTime) -> bool {
+        match self.last_draw {
+            Some(last_draw) => now.duration_since(last_draw).map(|elapsed| elapsed >= self.interval).unwrap_or(true),
+            None => true
+        }
+    }
+}
+
+impl Target for TargetTerminal {
+    fn base_target(&mut self) -> &mut BaseTarget {
+        &mut self.base_target
+    }
+
+    fn render(&mut self) -> bool {
+        let now = SystemTime::now();
+        if !self.is_due(now) {
+            return true;
+        }
+        self.last_draw = Some(now);
+        let dimensions = self.base_target.dimensions;
+        let pixels = read_pixels(&self.base_target.renderer, None, dimensions);
+        let frame = match self.style {
+            TerminalStyle::HalfBlock => draw_half_block(&pixels, dimensions),
+            TerminalStyle::Braille => draw_braille(&pixels, dimensions)
+        };
+        if frame != self.previous {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            if write!(stdout, "{}{}", CURSOR_HOME, frame).and_then(|_| stdout.flush()).is_err() {
+                return false;
+            }
+            self.previous = frame;
+        }
+        true
+    }
+}
+
+impl Drop for TargetTerminal {
+    fn drop(&mut self) {
+        print!("{}", SHOW_CURSOR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dimensions::Dimensions;
+    use test_helpers::*;
+
+    #[test]
+    fn draw_half_block() {
+        let pixels = create_test_pixels(3, 3, &[(0, 0), (0, 1), (1, 0), (2, 1), (0, 2)]);
+        assert_eq!("█▀▄\n▀  \n", super::draw_half_block(&pixels, Dimensions::new(3, 3)));
+    }
+
+    #[test]
+    fn draw_braille() {
+        let pixels = create_test_pixels(4, 4, &[(0, 0), (1, 3), (2, 1)]);
+        assert_eq!("\u{2881}\u{2802}\n", super::draw_braille(&pixels, Dimensions::new(4, 4)));
+    }
+}
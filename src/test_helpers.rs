@@ -2,6 +2,10 @@
 use sdl2::surface::Surface;
 use sdl2::render::Renderer;
 use sdl2::pixels::{PixelFormatEnum, Color};
+use chrono::{Local, Duration};
+use mpd::status::State;
+use info::Info;
+use network::Frame;
 
 pub fn create_test_renderer() -> Renderer<'static> {
     let surface = Surface::new(32, 16, PixelFormatEnum::RGBA8888).unwrap();
@@ -11,3 +15,33 @@ pub fn create_test_renderer() -> Renderer<'static> {
     renderer.set_draw_color(Color::RGBA(0, 0, 0, 255));
     renderer
 }
+
+/// Pixels of a frame, row by row, in which only the specified pixels are on.
+pub fn create_test_pixels(width: usize, height: usize, on: &[(usize, usize)]) -> Vec<bool> {
+    (0 .. width * height).map(|index| on.contains(&(index % width, index / width))).collect()
+}
+
+/// A frame as it is sent over the network, in which only the specified pixels are on.
+pub fn create_test_frame(sequence: u32, brightness: u8, width: u16, height: u16, on: &[(usize, usize)]) -> Frame {
+    Frame {
+        sequence: sequence,
+        brightness: brightness,
+        width: width,
+        height: height,
+        pixels: create_test_pixels(width as usize, height as usize, on)
+    }
+}
+
+pub fn create_test_info(connected: bool, state: State, volume: i8) -> Info {
+    Info {
+        connected: connected,
+        volume: volume,
+        time: Local::now(),
+        artist: String::new(),
+        song: String::new(),
+        song_id: None,
+        duration: Duration::zero(),
+        elapsed: Duration::zero(),
+        state: state
+    }
+}